@@ -1,5 +1,5 @@
 use why_rs::fcm::FCM;
-use why_rs::dag::{Value, Variable, DAG};
+use why_rs::dag::{Value, DAG};
 use why_rs::dag;
 use why_rs::mechanism::{EmpiricalRoot, LinearRegression};
 use rand::Rng;
@@ -1,8 +1,6 @@
-use why_rs::pc::PC;
 use why_rs::dag::{Value, DAG};
 use why_rs::{dag};
 use rand::Rng;
-use polars::prelude::*;
 use why_rs::fcm::FCM;
 use why_rs::mechanism::{EmpiricalRoot, LinearRegression};
 use why_rs::intervene;
@@ -1,4 +1,3 @@
-use std::env;
 use why_rs::fcm::FCM;
 use why_rs::dag::{Value, Variable, DAG};
 use why_rs::dag;
@@ -1,8 +1,8 @@
+use why_rs::independence::FisherZ;
 use why_rs::pc::PC;
 use why_rs::dag::{Value, DAG};
 use why_rs::{dag};
 use rand::Rng;
-use polars::prelude::*;
 use why_rs::fcm::FCM;
 use why_rs::mechanism::{EmpiricalRoot, LinearRegression};
 
@@ -25,7 +25,7 @@ fn main() {
     let df = fcm.sample(200);
     println!("df: {}", df);
 
-    let mut pc = PC::new(vec!["A".to_string(),"B".to_string(),"C".to_string(),"D".to_string()], df);
+    let mut pc = PC::new(vec!["A".to_string(),"B".to_string(),"C".to_string(),"D".to_string()], FisherZ::new(&df));
     pc.run(0.05);
     println!("{}", pc.graph);
 }
\ No newline at end of file
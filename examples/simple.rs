@@ -1,8 +1,7 @@
 use why_rs::fcm::FCM;
-use why_rs::dag::Value;
 
 fn main() {
-    let mut model = FCM::new()
+    let model = FCM::new()
         .node("A")
         .node("B")
         .edge("A", "B");
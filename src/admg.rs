@@ -0,0 +1,266 @@
+use std::collections::{BTreeSet, HashSet};
+use std::{fmt, fs};
+use std::ops::{Deref, DerefMut};
+use petgraph::Direction;
+use petgraph::algo::toposort;
+use petgraph::dot::dot_parser::{DotParsingError, ParseFromDot};
+use petgraph::graph::{DiGraph, NodeIndex};
+use petgraph::visit::EdgeRef;
+use crate::dag::{DagError, Variable, DAG};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    /// A -> B: A is a direct cause of B
+    Directed,
+    /// A <-> B: A and B share an unobserved common cause
+    Bidirected,
+}
+
+/// Acyclic directed mixed graph: a DAG over the observed variables plus bidirected
+/// edges standing in for hidden confounders.
+///
+/// Bidirected edges are stored once, in the direction they were added.
+#[derive(Clone)]
+pub struct ADMG {
+    pub graph: DiGraph<Variable, Edge>,
+}
+
+impl Deref for ADMG {
+    type Target = DiGraph<Variable, Edge>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.graph
+    }
+}
+
+impl DerefMut for ADMG {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.graph
+    }
+}
+
+impl Default for ADMG {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ADMG {
+    pub fn new() -> ADMG {
+        ADMG { graph: DiGraph::<Variable, Edge>::new() }
+    }
+
+    /// Reads a DOT file. Edges with `dir=both` or `style=dashed` become bidirected edges,
+    /// every other edge is directed.
+    pub fn from_dot(path: &str) -> Result<ADMG, DotParsingError> {
+        let string_graph = fs::read_to_string(path).unwrap();
+        let graph = <petgraph::Graph<_, _, _, _> as ParseFromDot>::try_from(&string_graph)?;
+
+        let graph: DiGraph<Variable, Edge> = graph.map(
+            |_, node_weight| Variable::from(node_weight.id.clone()),
+            |_, edge_weight| {
+                let bidirected = edge_weight.elems.iter().any(|(key, value)| {
+                    let value = value.trim_matches('"');
+                    (*key == "dir" && value == "both") || (*key == "style" && value == "dashed")
+                });
+                if bidirected { Edge::Bidirected } else { Edge::Directed }
+            });
+        Ok(ADMG { graph })
+    }
+
+    /// Every edge of the DAG becomes a directed edge
+    pub fn from_dag(dag: &DAG) -> ADMG {
+        ADMG { graph: dag.graph.map(|_, v| v.clone(), |_, _| Edge::Directed) }
+    }
+
+    /// Latent projection of a DAG onto its observed variables.
+    ///
+    /// A -> B is kept when B is reachable from A through latent nodes only, and A <-> B is added
+    /// when A and B have a common latent ancestor through latent-only paths.
+    pub fn latent_projection(dag: &DAG, latent: &[Variable]) -> Result<ADMG, DagError> {
+        for v in latent {
+            dag.get_index(v).ok_or_else(|| DagError::UnknownVariable(v.clone()))?;
+        }
+        let latent: HashSet<&Variable> = latent.iter().collect();
+
+        // Observed nodes reachable from `start` through latent-only directed paths
+        let observed_reach = |start: &Variable| -> BTreeSet<Variable> {
+            let mut found = BTreeSet::new();
+            let mut seen = HashSet::new();
+            let mut stack = dag.get_children(start);
+            while let Some(n) = stack.pop() {
+                if !seen.insert(n.clone()) { continue; }
+                if latent.contains(&n) {
+                    stack.extend(dag.get_children(&n));
+                } else {
+                    found.insert(n);
+                }
+            }
+            found
+        };
+
+        let mut admg = ADMG::new();
+        let observed: Vec<Variable> = dag.variables().into_iter().filter(|v| !latent.contains(v)).collect();
+        for v in &observed {
+            admg = admg.node(v);
+        }
+        for v in &observed {
+            for child in observed_reach(v) {
+                admg = admg.edge(v, &child);
+            }
+        }
+        for l in dag.variables().iter().filter(|v| latent.contains(v)) {
+            let confounded: Vec<Variable> = observed_reach(l).into_iter().collect();
+            for i in 0..confounded.len() {
+                for j in (i + 1)..confounded.len() {
+                    if !admg.has_bidirected(&confounded[i], &confounded[j]) {
+                        admg = admg.bidirected(&confounded[i], &confounded[j]);
+                    }
+                }
+            }
+        }
+        Ok(admg)
+    }
+
+    pub fn get_index(&self, variable: &Variable) -> Option<NodeIndex> {
+        self.graph.node_indices().find(|&node| self.graph[node].eq(variable))
+    }
+
+    pub fn node(mut self, name: &str) -> Self {
+        if self.get_index(&Variable::from(name)).is_none() {
+            self.graph.add_node(name.to_string());
+        }
+        self
+    }
+
+    pub fn edge(mut self, from: &str, to: &str) -> Self {
+        let from_idx = self.get_index(&from.to_string()).expect("Node not found");
+        let to_idx = self.get_index(&to.to_string()).expect("Node not found");
+        self.graph.add_edge(from_idx, to_idx, Edge::Directed);
+        self
+    }
+
+    pub fn bidirected(mut self, a: &str, b: &str) -> Self {
+        let a_idx = self.get_index(&a.to_string()).expect("Node not found");
+        let b_idx = self.get_index(&b.to_string()).expect("Node not found");
+        self.graph.add_edge(a_idx, b_idx, Edge::Bidirected);
+        self
+    }
+
+    pub fn variables(&self) -> Vec<Variable> {
+        self.graph.node_indices().map(|node| self.graph[node].clone()).collect()
+    }
+
+    /// Parents through directed edges only
+    pub fn get_parents(&self, node: &Variable) -> Vec<Variable> {
+        self.directed_neighbors(node, Direction::Incoming)
+    }
+
+    pub fn get_children(&self, node: &Variable) -> Vec<Variable> {
+        self.directed_neighbors(node, Direction::Outgoing)
+    }
+
+    /// Nodes joined to `node` by a bidirected edge
+    pub fn get_siblings(&self, node: &Variable) -> Vec<Variable> {
+        let idx = self.get_index(node).expect("Node not found");
+        self.graph.edges_directed(idx, Direction::Outgoing)
+            .filter(|e| *e.weight() == Edge::Bidirected)
+            .map(|e| self.graph[e.target()].clone())
+            .chain(self.graph.edges_directed(idx, Direction::Incoming)
+                .filter(|e| *e.weight() == Edge::Bidirected)
+                .map(|e| self.graph[e.source()].clone()))
+            .collect()
+    }
+
+    pub fn has_bidirected(&self, a: &Variable, b: &Variable) -> bool {
+        self.get_siblings(a).contains(b)
+    }
+
+    /// Topological order of the directed part, root nodes first; bidirected edges are ignored
+    pub fn sort(&self) -> Vec<Variable> {
+        match toposort(&self.directed_part().graph, None) {
+            Ok(indices) => indices.into_iter().map(|idx| self.graph[idx].clone()).collect(),
+            Err(_) => panic!("Graph contains a directed cycle! Cannot sort."),
+        }
+    }
+
+    /// The DAG formed by the directed edges (node indices are preserved)
+    pub fn directed_part(&self) -> DAG {
+        DAG { graph: self.graph.filter_map(
+            |_, v| Some(v.clone()),
+            |_, e| if *e == Edge::Directed { Some(()) } else { None },
+        )}
+    }
+
+    /// Districts (c-components): sets of nodes connected by bidirected paths, in topological order
+    pub fn districts(&self) -> Vec<Vec<Variable>> {
+        let mut districts = Vec::new();
+        let mut assigned = HashSet::new();
+        for start in self.sort() {
+            if assigned.contains(&start) { continue; }
+            let mut district = Vec::new();
+            let mut stack = vec![start];
+            while let Some(n) = stack.pop() {
+                if !assigned.insert(n.clone()) { continue; }
+                stack.extend(self.get_siblings(&n));
+                district.push(n);
+            }
+            district.sort();
+            districts.push(district);
+        }
+        districts
+    }
+
+    /// The canonical DAG: each bidirected edge A <-> B becomes A <- U -> B for a fresh latent U.
+    /// Returns the DAG and the names of the added latent nodes.
+    pub fn canonical_dag(&self) -> (DAG, Vec<Variable>) {
+        let mut dag = self.directed_part();
+        let mut latent = Vec::new();
+        for e in self.graph.edge_references().filter(|e| *e.weight() == Edge::Bidirected) {
+            let a = self.graph[e.source()].clone();
+            let b = self.graph[e.target()].clone();
+            let mut name = format!("U_{}_{}", a, b);
+            while dag.get_index(&name).is_some() {
+                name.push('\'');
+            }
+            dag = dag.node(&name).edge(&name, &a).edge(&name, &b);
+            latent.push(name);
+        }
+        (dag, latent)
+    }
+
+    /// m-separation: d-separation in the canonical DAG
+    pub fn is_m_separated(&self, xs: &[Variable], ys: &[Variable], given: &[Variable]) -> Result<bool, DagError> {
+        let (dag, _) = self.canonical_dag();
+        dag.is_d_separated(xs, ys, given)
+    }
+
+    fn directed_neighbors(&self, node: &Variable, direction: Direction) -> Vec<Variable> {
+        let idx = self.get_index(node).expect("Node not found");
+        self.graph.edges_directed(idx, direction)
+            .filter(|e| *e.weight() == Edge::Directed)
+            .map(|e| match direction {
+                Direction::Incoming => self.graph[e.source()].clone(),
+                Direction::Outgoing => self.graph[e.target()].clone(),
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for ADMG {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "ADMG {{")?;
+
+        for node_idx in self.graph.node_indices() {
+            let name = &self.graph[node_idx];
+            writeln!(f, "  {} -> {:?}", name, self.get_children(name))?;
+
+            let siblings = self.get_siblings(name);
+            if !siblings.is_empty() {
+                writeln!(f, "  {} <-> {:?}", name, siblings)?;
+            }
+        }
+
+        writeln!(f, "}}")
+    }
+}
@@ -0,0 +1,112 @@
+use std::collections::HashSet;
+use crate::dag::{DagError, Variable, DAG};
+use crate::pc::get_combinations;
+
+/// Checks Pearl's backdoor criterion for the effect of `treatment` on `outcome`:
+/// `set` contains no descendant of the treatment and blocks every path into the treatment.
+pub fn is_valid_adjustment_set(
+    dag: &DAG,
+    treatment: &Variable,
+    outcome: &Variable,
+    set: &[Variable],
+) -> Result<bool, DagError> {
+    let descendants = dag.descendants(std::slice::from_ref(treatment))?;
+    dag.get_index(outcome).ok_or_else(|| DagError::UnknownVariable(outcome.clone()))?;
+
+    if set.iter().any(|v| descendants.contains(v)) {
+        return Ok(false);
+    }
+
+    // Backdoor paths are exactly the paths that survive removing the treatment's outgoing edges
+    backdoor_graph(dag, std::slice::from_ref(treatment)).is_d_separated(
+        std::slice::from_ref(treatment),
+        std::slice::from_ref(outcome),
+        set,
+    )
+}
+
+/// Enumerates every minimal valid backdoor adjustment set, smallest first.
+///
+/// Minimal sets always lie inside the ancestors of the treatment and outcome, so only those
+/// are searched. The search is exhaustive over that pool and grows exponentially with its size.
+pub fn minimal_adjustment_sets(
+    dag: &DAG,
+    treatment: &Variable,
+    outcome: &Variable,
+) -> Result<Vec<Vec<Variable>>, DagError> {
+    let descendants = dag.descendants(std::slice::from_ref(treatment))?;
+    let mut pool: Vec<Variable> = dag.ancestors(&[treatment.clone(), outcome.clone()])?
+        .into_iter()
+        .filter(|v| v != treatment && v != outcome && !descendants.contains(v))
+        .collect();
+    pool.sort();
+
+    let backdoor = backdoor_graph(dag, std::slice::from_ref(treatment));
+    let mut minimal: Vec<Vec<Variable>> = Vec::new();
+
+    for size in 0..=pool.len() {
+        for candidate in get_combinations(&pool, size) {
+            // A superset of a minimal set is valid but not minimal
+            let contains_minimal = minimal.iter()
+                .any(|m| m.iter().all(|v| candidate.contains(v)));
+            if contains_minimal { continue; }
+
+            if backdoor.is_d_separated(
+                std::slice::from_ref(treatment),
+                std::slice::from_ref(outcome),
+                &candidate,
+            )? {
+                minimal.push(candidate);
+            }
+        }
+    }
+    Ok(minimal)
+}
+
+/// The optimal adjustment set ("O-set") of Henckel, Perković & Maathuis (2022):
+/// the parents of the causal nodes, minus the forbidden nodes.
+///
+/// Among all valid adjustment sets it gives the smallest asymptotic variance for
+/// linear estimators. Returns `None` when the outcome is not a descendant of the treatment.
+pub fn optimal_adjustment_set(
+    dag: &DAG,
+    treatment: &Variable,
+    outcome: &Variable,
+) -> Result<Option<Vec<Variable>>, DagError> {
+    let descendants = dag.descendants(std::slice::from_ref(treatment))?;
+    let outcome_ancestors = dag.ancestors(std::slice::from_ref(outcome))?;
+
+    if treatment == outcome || !descendants.contains(outcome) {
+        return Ok(None);
+    }
+
+    // Causal nodes: everything on a directed path treatment -> ... -> outcome, except the treatment
+    let causal: Vec<Variable> = descendants.intersection(&outcome_ancestors)
+        .filter(|v| *v != treatment)
+        .cloned()
+        .collect();
+
+    let mut forbidden = dag.descendants(&causal)?;
+    forbidden.insert(treatment.clone());
+
+    let mut o_set: Vec<Variable> = causal.iter()
+        .flat_map(|v| dag.get_parents(v))
+        .filter(|v| !forbidden.contains(v))
+        .collect::<HashSet<Variable>>()
+        .into_iter()
+        .collect();
+    o_set.sort();
+    Ok(Some(o_set))
+}
+
+// Copy of the graph with every edge leaving one of `nodes` removed
+pub(crate) fn backdoor_graph(dag: &DAG, nodes: &[Variable]) -> DAG {
+    let mut graph = dag.clone();
+    let sources: Vec<_> = nodes.iter()
+        .map(|v| graph.get_index(v).expect("Node not found"))
+        .collect();
+    graph.graph.retain_edges(|g, e| {
+        g.edge_endpoints(e).map(|(from, _)| !sources.contains(&from)).unwrap_or(true)
+    });
+    graph
+}
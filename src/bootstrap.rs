@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+use std::fmt;
+use polars::prelude::{DataFrame, IdxCa, IdxSize, PlSmallStr};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use log::info;
+use crate::dag::Variable;
+use crate::pdag::PDAG;
+
+/// Edge stability by bootstrap resampling: runs a discovery method on resamples of the data
+/// (rows drawn with replacement) and counts how often each edge appears, in which orientation.
+///
+/// Works with any learner that returns a `PDAG`, e.g.
+/// `|df| { let mut pc = PC::new(vars.clone(), FisherZ::new(df)); pc.run(0.05); pc.graph }`.
+pub struct Bootstrap {
+    n_resamples: usize,
+    seed: u64,
+}
+
+/// How often one pair of variables was found in each state, as fractions of the resamples.
+/// `forward` is A -> B and `backward` is B -> A for the pair (A, B) it was queried with.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct EdgeFrequency {
+    pub absent: f64,
+    pub forward: f64,
+    pub backward: f64,
+    pub undirected: f64,
+}
+
+impl EdgeFrequency {
+    /// Fraction of resamples where the pair was adjacent, in any orientation
+    pub fn present(&self) -> f64 {
+        self.forward + self.backward + self.undirected
+    }
+
+    fn reversed(self) -> Self {
+        EdgeFrequency { forward: self.backward, backward: self.forward, ..self }
+    }
+}
+
+/// The result of a [`Bootstrap`] run
+pub struct EdgeStability {
+    variables: Vec<Variable>,
+    pub n_resamples: usize,
+    // Keyed by (A, B) with A < B
+    frequencies: HashMap<(Variable, Variable), EdgeFrequency>,
+}
+
+impl Bootstrap {
+    pub fn new(n_resamples: usize) -> Self {
+        assert!(n_resamples > 0, "Need at least one resample");
+        Bootstrap { n_resamples, seed: 0 }
+    }
+
+    /// Seed for drawing the resamples, so results are reproducible (default 0)
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn run<F: Fn(&DataFrame) -> PDAG>(&self, data: &DataFrame, learner: F) -> EdgeStability {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let n = data.height();
+        let mut counts: HashMap<(Variable, Variable), [usize; 3]> = HashMap::new();
+        let mut variables: Vec<Variable> = Vec::new();
+
+        for b in 0..self.n_resamples {
+            info!("Bootstrap Resample {}/{}", b + 1, self.n_resamples);
+            let rows: Vec<IdxSize> = (0..n).map(|_| rng.gen_range(0..n) as IdxSize).collect();
+            let resample = data.take(&IdxCa::from_vec(PlSmallStr::from("rows"), rows))
+                .expect("Failed to resample the DataFrame");
+
+            let graph = learner(&resample);
+            variables = graph.variables();
+            variables.sort();
+
+            // [A -> B, B -> A, A - B] for each pair A < B
+            for (i, a) in variables.iter().enumerate() {
+                for c in &variables[i + 1..] {
+                    let state = if graph.is_directed(a, c) { 0 }
+                        else if graph.is_directed(c, a) { 1 }
+                        else if graph.is_undirected(a, c) { 2 }
+                        else { continue };
+                    counts.entry((a.clone(), c.clone())).or_default()[state] += 1;
+                }
+            }
+        }
+
+        let total = self.n_resamples as f64;
+        let frequencies = counts.into_iter()
+            .map(|(pair, [forward, backward, undirected])| {
+                let absent = self.n_resamples - forward - backward - undirected;
+                (pair, EdgeFrequency {
+                    absent: absent as f64 / total,
+                    forward: forward as f64 / total,
+                    backward: backward as f64 / total,
+                    undirected: undirected as f64 / total,
+                })
+            })
+            .collect();
+
+        EdgeStability { variables, n_resamples: self.n_resamples, frequencies }
+    }
+}
+
+impl EdgeStability {
+    /// Frequencies for the pair (A, B)
+    pub fn edge(&self, a: &str, b: &str) -> EdgeFrequency {
+        let (key, flipped) = if a <= b { ((a.to_string(), b.to_string()), false) } else { ((b.to_string(), a.to_string()), true) };
+        let freq = self.frequencies.get(&key).copied()
+            .unwrap_or(EdgeFrequency { absent: 1.0, ..Default::default() });
+        if flipped { freq.reversed() } else { freq }
+    }
+
+    /// Every pair that was adjacent in at least one resample, as (A, B, frequencies), sorted
+    pub fn edges(&self) -> Vec<(Variable, Variable, EdgeFrequency)> {
+        let mut edges: Vec<(Variable, Variable, EdgeFrequency)> = self.frequencies.iter()
+            .map(|((a, b), f)| (a.clone(), b.clone(), *f))
+            .collect();
+        edges.sort_by(|x, y| (&x.0, &x.1).cmp(&(&y.0, &y.1)));
+        edges
+    }
+
+    /// Keeps the pairs that were adjacent in at least `threshold` of the resamples. Such an edge
+    /// is directed if one orientation alone reaches the threshold (the more frequent one, when
+    /// both do), otherwise undirected.
+    pub fn consensus(&self, threshold: f64) -> PDAG {
+        let mut graph = PDAG::new();
+        for v in &self.variables {
+            graph.add_node(v.clone());
+        }
+
+        for (a, b, freq) in self.edges() {
+            if freq.present() < threshold { continue; }
+            if freq.forward >= threshold && freq.forward > freq.backward {
+                graph.add_directed(&a, &b);
+            } else if freq.backward >= threshold && freq.backward > freq.forward {
+                graph.add_directed(&b, &a);
+            } else {
+                graph.add_undirected(&a, &b);
+            }
+        }
+        graph
+    }
+}
+
+impl fmt::Display for EdgeStability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "EdgeStability ({} resamples) {{", self.n_resamples)?;
+
+        for (a, b, freq) in self.edges() {
+            writeln!(f, "  {} - {}: absent {:.2}, {} -> {} {:.2}, {} <- {} {:.2}, undirected {:.2}",
+                     a, b, freq.absent, a, b, freq.forward, a, b, freq.backward, freq.undirected)?;
+        }
+
+        writeln!(f, "}}")
+    }
+}
@@ -1,18 +1,37 @@
 use std::{fmt, fs};
+use std::collections::{HashMap, HashSet};
 use std::ops::{Deref, DerefMut};
 use petgraph::Direction;
-use petgraph::graph::DiGraph;
+use petgraph::graph::{DiGraph, NodeIndex};
 use petgraph::visit::EdgeRef;
 use petgraph::algo::toposort;
-use dot_parser::*;
 use petgraph::dot::dot_parser::ParseFromDot;
 use petgraph::dot::dot_parser::DotParsingError;
-use polars::prelude::DataFrame;
-use polars::prelude::GroupByMethod::Var;
 
 pub type Variable = String;
 
 pub type Value = f64;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DagError {
+    /// The variable name does not match any node in the graph
+    UnknownVariable(Variable),
+    /// The variable appears in more than one of the sets passed to a query
+    OverlappingSets(Variable),
+}
+
+impl fmt::Display for DagError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DagError::UnknownVariable(v) => write!(f, "unknown variable '{}'", v),
+            DagError::OverlappingSets(v) => write!(f, "variable '{}' appears in more than one set", v),
+        }
+    }
+}
+
+impl std::error::Error for DagError {}
+
+#[derive(Clone)]
 pub struct DAG {
     pub graph: DiGraph<Variable, ()>
 }
@@ -33,6 +52,12 @@ impl DerefMut for DAG {
 
 
 
+impl Default for DAG {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl DAG {
     pub fn new() -> DAG {
         DAG { graph: DiGraph::<Variable, ()>::new()}
@@ -40,7 +65,7 @@ impl DAG {
 
     pub fn from_dot(path: &str) -> Result<DAG, DotParsingError> {
         let string_graph = fs::read_to_string(path).unwrap();
-        return match <petgraph::Graph<_, _, _, _> as ParseFromDot>::try_from(&string_graph) {
+        match <petgraph::Graph<_, _, _, _> as ParseFromDot>::try_from(&string_graph) {
             Ok(graph) => {
                 let dgraph: DiGraph<Variable, ()> = graph.map(
                     // 1. Node Conversion Closure
@@ -54,7 +79,6 @@ impl DAG {
                     |_, _edge_weight| {
                         // edge_weight is Type: DotAttrList
                         // We return () to satisfy the target type DiGraph<Variable, ()>
-                        ()
                     });
                 Ok(DAG { graph: dgraph }) // let why_graph = DAG { graph: DiGraph::from(graph)}
             },
@@ -123,6 +147,116 @@ impl DAG {
         }
     }
 
+    pub fn get_children(&self, node: &Variable) -> Vec<Variable> {
+        let node_index = self.get_index(node).expect("Node not found");
+        self.graph.neighbors_directed(node_index, Direction::Outgoing)
+            .map(|neighbor_index| self.graph[neighbor_index].clone())
+            .collect()
+    }
+
+    /// All ancestors of the given nodes, including the nodes themselves
+    pub fn ancestors(&self, nodes: &[Variable]) -> Result<HashSet<Variable>, DagError> {
+        let start = self.resolve(nodes)?;
+        Ok(self.reach(&start, Direction::Incoming)
+            .into_iter()
+            .map(|idx| self.graph[idx].clone())
+            .collect())
+    }
+
+    /// All descendants of the given nodes, including the nodes themselves
+    pub fn descendants(&self, nodes: &[Variable]) -> Result<HashSet<Variable>, DagError> {
+        let start = self.resolve(nodes)?;
+        Ok(self.reach(&start, Direction::Outgoing)
+            .into_iter()
+            .map(|idx| self.graph[idx].clone())
+            .collect())
+    }
+
+    /// Returns true if every node in `xs` is d-separated from every node in `ys` given `given`.
+    ///
+    /// Uses the reachability ("Bayes-ball") procedure from Koller & Friedman, Algorithm 3.1:
+    /// the three sets must be disjoint and refer to nodes in the graph.
+    pub fn is_d_separated(&self, xs: &[Variable], ys: &[Variable], given: &[Variable]) -> Result<bool, DagError> {
+        let x_idx = self.resolve(xs)?;
+        let y_idx = self.resolve(ys)?;
+        let z_idx = self.resolve(given)?;
+
+        // The sets have to be disjoint for the query to make sense
+        let x_set: HashSet<NodeIndex> = x_idx.iter().cloned().collect();
+        let y_set: HashSet<NodeIndex> = y_idx.iter().cloned().collect();
+        let observed: HashSet<NodeIndex> = z_idx.iter().cloned().collect();
+        let overlap = x_set.intersection(&y_set)
+            .chain(x_set.intersection(&observed))
+            .chain(y_set.intersection(&observed))
+            .next();
+        if let Some(idx) = overlap {
+            return Err(DagError::OverlappingSets(self.graph[*idx].clone()));
+        }
+
+        let reachable = self.reachable_from(&x_idx, &observed);
+        Ok(y_idx.iter().all(|y| !reachable.contains(y)))
+    }
+
+    // Nodes with an active trail from `sources` given `observed`
+    fn reachable_from(&self, sources: &[NodeIndex], observed: &HashSet<NodeIndex>) -> HashSet<NodeIndex> {
+        // Phase 1: observed nodes and their ancestors (these let a collider pass the ball)
+        let z: Vec<NodeIndex> = observed.iter().cloned().collect();
+        let observed_ancestors = self.reach(&z, Direction::Incoming);
+
+        // Phase 2: traverse (node, direction) pairs.
+        // `true` means we arrived from a child (travelling up), `false` from a parent (down)
+        let mut to_visit: Vec<(NodeIndex, bool)> = sources.iter().map(|&x| (x, true)).collect();
+        let mut visited = HashSet::new();
+        let mut reachable = HashSet::new();
+
+        while let Some((node, up)) = to_visit.pop() {
+            if !visited.insert((node, up)) { continue; }
+
+            let is_observed = observed.contains(&node);
+            if !is_observed { reachable.insert(node); }
+
+            if up && !is_observed {
+                for parent in self.graph.neighbors_directed(node, Direction::Incoming) {
+                    to_visit.push((parent, true));
+                }
+                for child in self.graph.neighbors_directed(node, Direction::Outgoing) {
+                    to_visit.push((child, false));
+                }
+            } else if !up {
+                if !is_observed {
+                    for child in self.graph.neighbors_directed(node, Direction::Outgoing) {
+                        to_visit.push((child, false));
+                    }
+                }
+                // v-structure: activated when the node or one of its descendants is observed
+                if observed_ancestors.contains(&node) {
+                    for parent in self.graph.neighbors_directed(node, Direction::Incoming) {
+                        to_visit.push((parent, true));
+                    }
+                }
+            }
+        }
+        reachable
+    }
+
+    // Every node reachable from `start` following edges in `direction` (start included)
+    fn reach(&self, start: &[NodeIndex], direction: Direction) -> HashSet<NodeIndex> {
+        let mut seen: HashSet<NodeIndex> = HashSet::new();
+        let mut stack: Vec<NodeIndex> = start.to_vec();
+        while let Some(node) = stack.pop() {
+            if seen.insert(node) {
+                stack.extend(self.graph.neighbors_directed(node, direction));
+            }
+        }
+        seen
+    }
+
+    fn resolve(&self, nodes: &[Variable]) -> Result<Vec<NodeIndex>, DagError> {
+        nodes.iter()
+            .map(|v| self.get_index(v).ok_or_else(|| DagError::UnknownVariable(v.clone())))
+            .collect()
+    }
+
 }
 
 impl fmt::Display for DAG {
@@ -143,4 +277,52 @@ impl fmt::Display for DAG {
 
         writeln!(f, "}}")
     }
-}
\ No newline at end of file
+}
+
+/// A DAG with a linear coefficient on every edge, as returned by the linear discovery methods
+#[derive(Clone)]
+pub struct WeightedDAG {
+    pub dag: DAG,
+    /// Coefficient of each edge, keyed by (from, to)
+    pub weights: HashMap<(Variable, Variable), f64>,
+    /// Mean of each variable in the data the weights were estimated on
+    pub means: HashMap<Variable, f64>,
+}
+
+impl WeightedDAG {
+    pub fn weight(&self, from: &str, to: &str) -> Option<f64> {
+        self.weights.get(&(from.to_string(), to.to_string())).copied()
+    }
+
+    /// Weights on the incoming edges of `node`, ordered like its alphabetically sorted parents
+    /// (the order `FCM::sample` passes parent values to a mechanism in)
+    pub fn parent_weights(&self, node: &Variable) -> Vec<f64> {
+        let mut parents = self.dag.get_parents(node);
+        parents.sort();
+        parents.iter()
+            .map(|p| self.weight(p, node).expect("Edge has no weight"))
+            .collect()
+    }
+
+    /// Constant term of `node`'s structural equation, `mean(node) - Σ weight · mean(parent)`,
+    /// so the model reproduces the means of the data the weights were estimated on
+    pub fn intercept(&self, node: &Variable) -> f64 {
+        let mean = |v: &Variable| self.means.get(v).copied().unwrap_or(0.0);
+        self.dag.get_parents(node).iter()
+            .fold(mean(node), |acc, p| acc - self.weight(p, node).expect("Edge has no weight") * mean(p))
+    }
+}
+
+impl fmt::Display for WeightedDAG {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "WeightedDAG {{")?;
+
+        let mut edges: Vec<(&(Variable, Variable), &f64)> = self.weights.iter().collect();
+        edges.sort_by(|a, b| a.0.cmp(b.0));
+        for ((from, to), weight) in edges {
+            writeln!(f, "  {} -> {} ({:.3})", from, to, weight)?;
+        }
+
+        writeln!(f, "}}")
+    }
+}
@@ -0,0 +1,470 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use log::info;
+use crate::dag::Variable;
+use crate::independence::CITest;
+use crate::pag::{Mark, PAG};
+use crate::pc::{get_combinations, PC};
+
+/// Fast Causal Inference (Spirtes et al., 2000) with the complete orientation rules of
+/// Zhang (2008). Unlike PC it does not assume causal sufficiency: the result is a PAG whose
+/// edges allow for hidden common causes and selection bias.
+pub struct FCI {
+    /// Learned PAG
+    pub graph: PAG,
+    pub sepsets: HashMap<(Variable, Variable), HashSet<Variable>>,
+    /// Runs the skeleton phase, shared with PC
+    skeleton: PC,
+}
+
+impl FCI {
+    /// `test` decides conditional independence, e.g. `FisherZ::new(&df)` for Gaussian data
+    pub fn new<T: CITest + 'static>(variables: Vec<Variable>, test: T) -> Self {
+        FCI {
+            graph: PAG::new(),
+            sepsets: HashMap::new(),
+            skeleton: PC::new(variables, test),
+        }
+    }
+
+    /// Run the full pipeline; `alpha` is the significance level of the independence tests
+    pub fn run(&mut self, alpha: f64) {
+        assert!(alpha > 0.0 && alpha < 1.0, "alpha must be between 0 and 1, got {}", alpha);
+
+        info!("Phase 1: Learning Skeleton...");
+        self.skeleton.learn_skeleton(alpha);
+        self.sepsets = self.skeleton.sepsets.clone();
+        self.reset_marks();
+
+        info!("Phase 2: Possible-D-SEP...");
+        self.orient_colliders();
+        self.possible_dsep_removal(alpha);
+        self.reset_marks();
+
+        info!("Phase 3: Orienting Colliders...");
+        self.orient_colliders();
+
+        info!("Phase 4: Orientation Rules...");
+        self.apply_rules();
+    }
+
+    // Every remaining adjacency of the skeleton becomes o-o
+    fn reset_marks(&mut self) {
+        let variables = self.sorted_variables();
+        let mut pag = PAG::new();
+        for v in &variables {
+            pag = pag.node(v);
+        }
+        for (i, a) in variables.iter().enumerate() {
+            for b in &variables[i + 1..] {
+                if self.skeleton.graph.is_adjacent(a, b) {
+                    pag.add_edge(a, b, Mark::Circle, Mark::Circle);
+                }
+            }
+        }
+        self.graph = pag;
+    }
+
+    // ==========================================
+    // Possible-D-SEP
+    // ==========================================
+    fn possible_dsep_removal(&mut self, alpha: f64) {
+        let variables = self.sorted_variables();
+        // Computed once, from the PAG after the first collider orientation
+        let pds: HashMap<Variable, Vec<Variable>> = variables.iter()
+            .map(|v| (v.clone(), self.possible_dsep(v)))
+            .collect();
+
+        for x in &variables {
+            for y in self.graph.adjacent(x) {
+                if *x >= y { continue; }
+
+                'edge: for (a, b) in [(x, &y), (&y, x)] {
+                    let pool: Vec<Variable> = pds[a].iter().filter(|v| *v != b).cloned().collect();
+                    for size in 0..=pool.len() {
+                        for sepset in get_combinations(&pool, size) {
+                            if self.skeleton.is_independent(x, &y, &sepset, alpha).0 {
+                                info!("Possible-D-SEP: removed {} - {} given {:?}", x, y, sepset);
+                                self.skeleton.graph.remove_edge(x, &y);
+                                self.graph.remove_edge(x, &y);
+                                self.sepsets.insert((x.clone(), y.clone()), sepset.into_iter().collect());
+                                break 'edge;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // Nodes Z reachable from X by a path on which every inner node is a collider or the middle
+    // of a triangle (Spirtes et al., 2000)
+    fn possible_dsep(&self, x: &str) -> Vec<Variable> {
+        let mut found: HashSet<Variable> = HashSet::new();
+        let mut visited: HashSet<(Variable, Variable)> = HashSet::new();
+        let mut queue: VecDeque<(Variable, Variable)> = VecDeque::new();
+
+        for v in self.graph.adjacent(x) {
+            found.insert(v.clone());
+            visited.insert((x.to_string(), v.clone()));
+            queue.push_back((x.to_string(), v));
+        }
+
+        while let Some((u, v)) = queue.pop_front() {
+            for w in self.graph.adjacent(&v) {
+                if w == u || w == x { continue; }
+                let collider = self.graph.mark(&u, &v) == Some(Mark::Arrow)
+                    && self.graph.mark(&w, &v) == Some(Mark::Arrow);
+                if (collider || self.graph.is_adjacent(&u, &w)) && visited.insert((v.clone(), w.clone())) {
+                    found.insert(w.clone());
+                    queue.push_back((v.clone(), w));
+                }
+            }
+        }
+
+        let mut found: Vec<Variable> = found.into_iter().collect();
+        found.sort();
+        found
+    }
+
+    // ==========================================
+    // Rule 0: Unshielded Colliders
+    // ==========================================
+    fn orient_colliders(&mut self) {
+        for b in self.sorted_variables() {
+            let neighbors = self.graph.adjacent(&b);
+            for (i, a) in neighbors.iter().enumerate() {
+                for c in &neighbors[i + 1..] {
+                    if self.graph.is_adjacent(a, c) { continue; }
+                    let Some(sepset) = self.sepsets.get(&(a.clone(), c.clone())) else { continue; };
+                    if !sepset.contains(&b) {
+                        self.graph.set_mark(a, &b, Mark::Arrow);
+                        self.graph.set_mark(c, &b, Mark::Arrow);
+                        info!("Oriented Collider: {} *-> {} <-* {}", a, b, c);
+                    }
+                }
+            }
+        }
+    }
+
+    // ==========================================
+    // Rules 1-10 (Zhang, 2008)
+    // ==========================================
+    fn apply_rules(&mut self) {
+        let mut change = true;
+        while change {
+            change = false;
+            for (rule, apply) in [
+                (1, Self::rule_1 as fn(&mut Self) -> bool),
+                (2, Self::rule_2),
+                (3, Self::rule_3),
+                (4, Self::rule_4),
+                (5, Self::rule_5),
+                (6, Self::rule_6),
+                (7, Self::rule_7),
+                (8, Self::rule_8),
+                (9, Self::rule_9),
+                (10, Self::rule_10),
+            ] {
+                if apply(self) {
+                    info!("FCI Rule {} applied", rule);
+                    change = true;
+                }
+            }
+        }
+    }
+
+    // R1: A *-> B o-* C, A and C not adjacent => B -> C
+    fn rule_1(&mut self) -> bool {
+        let mut changed = false;
+        for b in self.sorted_variables() {
+            for a in self.graph.adjacent(&b) {
+                for c in self.graph.adjacent(&b) {
+                    if a == c || self.graph.is_adjacent(&a, &c) { continue; }
+                    if self.is(&a, &b, Mark::Arrow) && self.is(&c, &b, Mark::Circle) {
+                        self.graph.set_mark(&c, &b, Mark::Tail);
+                        self.graph.set_mark(&b, &c, Mark::Arrow);
+                        changed = true;
+                    }
+                }
+            }
+        }
+        changed
+    }
+
+    // R2: A -> B *-> C or A *-> B -> C, and A *-o C => A *-> C
+    fn rule_2(&mut self) -> bool {
+        let mut changed = false;
+        for (a, c) in self.ordered_pairs() {
+            if !self.is(&a, &c, Mark::Circle) { continue; }
+            let found = self.common_neighbors(&a, &c).iter().any(|b| {
+                (self.graph.is_directed(&a, b) && self.is(b, &c, Mark::Arrow))
+                    || (self.is(&a, b, Mark::Arrow) && self.graph.is_directed(b, &c))
+            });
+            if found {
+                self.graph.set_mark(&a, &c, Mark::Arrow);
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    // R3: A *-> B <-* C, A *-o D o-* C, A and C not adjacent, D *-o B => D *-> B
+    fn rule_3(&mut self) -> bool {
+        let mut changed = false;
+        for (d, b) in self.ordered_pairs() {
+            if !self.is(&d, &b, Mark::Circle) { continue; }
+            let common = self.common_neighbors(&d, &b);
+            let found = common.iter().enumerate().any(|(i, a)| {
+                common[i + 1..].iter().any(|c| {
+                    !self.graph.is_adjacent(a, c)
+                        && self.is(a, &b, Mark::Arrow) && self.is(c, &b, Mark::Arrow)
+                        && self.is(a, &d, Mark::Circle) && self.is(c, &d, Mark::Circle)
+                })
+            });
+            if found {
+                self.graph.set_mark(&d, &b, Mark::Arrow);
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    // R4: <T, ..., A, B, C> a discriminating path for B and B o-* C: B -> C if B is in
+    // sepset(T, C), A <-> B <-> C otherwise
+    fn rule_4(&mut self) -> bool {
+        for (b, c) in self.ordered_pairs() {
+            if !self.is(&c, &b, Mark::Circle) { continue; }
+            for a in self.common_neighbors(&b, &c) {
+                if !self.is(&b, &a, Mark::Arrow) || !self.graph.is_directed(&a, &c) { continue; }
+                let Some(t) = self.discriminating_start(&a, &b, &c) else { continue; };
+
+                let key = if t < c { (t.clone(), c.clone()) } else { (c.clone(), t.clone()) };
+                if self.sepsets.get(&key).is_some_and(|s| s.contains(&b)) {
+                    self.graph.set_mark(&c, &b, Mark::Tail);
+                    self.graph.set_mark(&b, &c, Mark::Arrow);
+                } else {
+                    self.graph.set_mark(&a, &b, Mark::Arrow);
+                    self.graph.set_mark(&b, &c, Mark::Arrow);
+                    self.graph.set_mark(&c, &b, Mark::Arrow);
+                }
+                return true;
+            }
+        }
+        false
+    }
+
+    // First node T of a discriminating path <T, ..., A, B, C>: every node between T and B is a
+    // collider on the path and a parent of C, and T is not adjacent to C
+    fn discriminating_start(&self, a: &str, b: &str, c: &str) -> Option<Variable> {
+        let mut visited: HashSet<Variable> = [a, b, c].iter().map(|v| v.to_string()).collect();
+        let mut queue = VecDeque::from([a.to_string()]);
+
+        while let Some(v) = queue.pop_front() {
+            for w in self.graph.adjacent(&v) {
+                if visited.contains(&w) || !self.is(&w, &v, Mark::Arrow) { continue; }
+                if !self.graph.is_adjacent(&w, c) {
+                    return Some(w);
+                }
+                if self.graph.is_directed(&w, c) && self.is(&v, &w, Mark::Arrow) {
+                    visited.insert(w.clone());
+                    queue.push_back(w);
+                }
+            }
+        }
+        None
+    }
+
+    // R5: A o-o B and an uncovered circle path <A, C, ..., D, B> with A, D and B, C not adjacent
+    // => A - B and every edge on the path becomes a tail-tail edge
+    fn rule_5(&mut self) -> bool {
+        for (a, b) in self.ordered_pairs() {
+            if a > b || !self.is_circle_edge(&a, &b) { continue; }
+            for c in self.graph.adjacent(&a) {
+                if c == b || self.graph.is_adjacent(&c, &b) || !self.is_circle_edge(&a, &c) { continue; }
+
+                let circle = |u: &str, v: &str| self.is_circle_edge(u, v);
+                let closes = |path: &[Variable]| !self.graph.is_adjacent(&a, &path[path.len() - 2]);
+                let Some(path) = self.uncovered_path(&mut vec![a.clone(), c], &b, &circle, &closes) else { continue; };
+
+                self.graph.set_mark(&a, &b, Mark::Tail);
+                self.graph.set_mark(&b, &a, Mark::Tail);
+                for pair in path.windows(2) {
+                    self.graph.set_mark(&pair[0], &pair[1], Mark::Tail);
+                    self.graph.set_mark(&pair[1], &pair[0], Mark::Tail);
+                }
+                return true;
+            }
+        }
+        false
+    }
+
+    // R6: A - B o-* C => B -* C
+    fn rule_6(&mut self) -> bool {
+        let mut changed = false;
+        for (a, b) in self.ordered_pairs() {
+            if !(self.is(&a, &b, Mark::Tail) && self.is(&b, &a, Mark::Tail)) { continue; }
+            for c in self.graph.adjacent(&b) {
+                if c != a && self.is(&c, &b, Mark::Circle) {
+                    self.graph.set_mark(&c, &b, Mark::Tail);
+                    changed = true;
+                }
+            }
+        }
+        changed
+    }
+
+    // R7: A -o B o-* C, A and C not adjacent => B -* C
+    fn rule_7(&mut self) -> bool {
+        let mut changed = false;
+        for (a, b) in self.ordered_pairs() {
+            if !(self.is(&b, &a, Mark::Tail) && self.is(&a, &b, Mark::Circle)) { continue; }
+            for c in self.graph.adjacent(&b) {
+                if c != a && !self.graph.is_adjacent(&a, &c) && self.is(&c, &b, Mark::Circle) {
+                    self.graph.set_mark(&c, &b, Mark::Tail);
+                    changed = true;
+                }
+            }
+        }
+        changed
+    }
+
+    // R8: A -> B -> C or A -o B -> C, and A o-> C => A -> C
+    fn rule_8(&mut self) -> bool {
+        let mut changed = false;
+        for (a, c) in self.ordered_pairs() {
+            if !self.is_partially_directed(&a, &c) { continue; }
+            let found = self.common_neighbors(&a, &c).iter().any(|b| {
+                self.is(b, &a, Mark::Tail)
+                    && (self.is(&a, b, Mark::Arrow) || self.is(&a, b, Mark::Circle))
+                    && self.graph.is_directed(b, &c)
+            });
+            if found {
+                self.graph.set_mark(&c, &a, Mark::Tail);
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    // R9: A o-> C and an uncovered potentially directed path <A, B, ..., C> with B and C not
+    // adjacent => A -> C
+    fn rule_9(&mut self) -> bool {
+        let mut changed = false;
+        for (a, c) in self.ordered_pairs() {
+            if !self.is_partially_directed(&a, &c) { continue; }
+            let found = self.graph.adjacent(&a).into_iter().any(|b| {
+                b != c && !self.graph.is_adjacent(&b, &c) && self.potentially_directed(&a, &b)
+                    && self.uncovered_path(
+                        &mut vec![a.clone(), b], &c,
+                        &|u, v| self.potentially_directed(u, v), &|_| true,
+                    ).is_some()
+            });
+            if found {
+                self.graph.set_mark(&c, &a, Mark::Tail);
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    // R10: A o-> C, B -> C <- D, uncovered potentially directed paths from A to B and from A to D
+    // starting with M and W respectively, M != W and M, W not adjacent => A -> C
+    fn rule_10(&mut self) -> bool {
+        let mut changed = false;
+        for (a, c) in self.ordered_pairs() {
+            if !self.is_partially_directed(&a, &c) { continue; }
+            let parents: Vec<Variable> = self.graph.adjacent(&c).into_iter()
+                .filter(|p| *p != a && self.graph.is_directed(p, &c))
+                .collect();
+            if parents.len() < 2 { continue; }
+
+            // For each parent of C, the nodes that can start an uncovered pd path to it from A
+            let starts: Vec<Vec<Variable>> = parents.iter()
+                .map(|p| self.graph.adjacent(&a).into_iter()
+                    .filter(|m| self.potentially_directed(&a, m) && (m == p || self.uncovered_path(
+                        &mut vec![a.clone(), m.clone()], p,
+                        &|u, v| self.potentially_directed(u, v), &|_| true,
+                    ).is_some()))
+                    .collect())
+                .collect();
+
+            let found = (0..parents.len()).any(|i| (i + 1..parents.len()).any(|j| {
+                starts[i].iter().any(|m| starts[j].iter().any(|w| m != w && !self.graph.is_adjacent(m, w)))
+            }));
+            if found {
+                self.graph.set_mark(&c, &a, Mark::Tail);
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    // ==========================================
+    // Path Helpers
+    // ==========================================
+
+    // Extends `path` to `end` through edges accepted by `step`, keeping every consecutive triple
+    // unshielded; `accept` gets the final say on a completed path
+    fn uncovered_path(
+        &self,
+        path: &mut Vec<Variable>,
+        end: &str,
+        step: &dyn Fn(&str, &str) -> bool,
+        accept: &dyn Fn(&[Variable]) -> bool,
+    ) -> Option<Vec<Variable>> {
+        let last = path.last().expect("Path must not be empty").clone();
+        for next in self.graph.adjacent(&last) {
+            if path.contains(&next) || !step(&last, &next) { continue; }
+            if path.len() >= 2 && self.graph.is_adjacent(&path[path.len() - 2], &next) { continue; }
+
+            path.push(next.clone());
+            if next == end {
+                if accept(path) { return Some(path.clone()); }
+            } else if let Some(found) = self.uncovered_path(path, end, step, accept) {
+                return Some(found);
+            }
+            path.pop();
+        }
+        None
+    }
+
+    // The edge U - V can be followed from U to V on a potentially directed path
+    fn potentially_directed(&self, u: &str, v: &str) -> bool {
+        self.graph.mark(v, u) != Some(Mark::Arrow) && self.graph.mark(u, v) != Some(Mark::Tail)
+    }
+
+    // A o-> C
+    fn is_partially_directed(&self, a: &str, c: &str) -> bool {
+        self.is(c, a, Mark::Circle) && self.is(a, c, Mark::Arrow)
+    }
+
+    fn is_circle_edge(&self, a: &str, b: &str) -> bool {
+        self.is(a, b, Mark::Circle) && self.is(b, a, Mark::Circle)
+    }
+
+    // The mark at B on the edge between A and B is `mark`
+    fn is(&self, a: &str, b: &str, mark: Mark) -> bool {
+        self.graph.mark(a, b) == Some(mark)
+    }
+
+    fn common_neighbors(&self, a: &str, b: &str) -> Vec<Variable> {
+        self.graph.adjacent(a).into_iter().filter(|n| n != b && self.graph.is_adjacent(n, b)).collect()
+    }
+
+    // Every adjacent pair, in both orders
+    fn ordered_pairs(&self) -> Vec<(Variable, Variable)> {
+        let mut pairs = Vec::new();
+        for a in self.sorted_variables() {
+            for b in self.graph.adjacent(&a) {
+                pairs.push((a.clone(), b));
+            }
+        }
+        pairs
+    }
+
+    fn sorted_variables(&self) -> Vec<Variable> {
+        let mut variables = self.skeleton.graph.variables();
+        variables.sort();
+        variables
+    }
+}
@@ -3,11 +3,9 @@ use std::ops::{Deref, DerefMut};
 use petgraph::graph::DiGraph;
 use polars::frame::DataFrame;
 use polars::prelude::{Column, NamedFrom, PlSmallStr, Series};
-pub use crate::dag::{DAG, Variable, Value};
-use rand::Rng;
+pub use crate::dag::{DAG, Variable, Value, WeightedDAG};
 use crate::intervention::Intervention;
-use petgraph::algo::toposort;
-use crate::mechanism::Mechanism;
+use crate::mechanism::{LinearRegression, Mechanism};
 
 pub struct FCM {
     pub graph: DAG,
@@ -30,6 +28,12 @@ impl DerefMut for FCM {
     }
 }
 
+impl Default for FCM {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl FCM {
     pub fn new() -> Self {
         FCM {
@@ -45,6 +49,20 @@ impl FCM {
         }
     }
 
+    /// Linear FCM with the learned edge weights, e.g. from `DirectLiNGAM`.
+    /// Every variable gets a `LinearRegression` mechanism whose bias is its intercept, so samples
+    /// keep the means of the original data. The noise is Gaussian with std `noise` for every
+    /// variable: an approximation, since LiNGAM assumes non-Gaussian noise and does not estimate
+    /// its distribution. With `noise = 0.0` every root is constant at its mean.
+    pub fn from_weighted_dag(graph: &WeightedDAG, noise: f64) -> Self {
+        let mut fcm = FCM::from_dag(graph.dag.clone());
+        for v in graph.dag.variables() {
+            let mech = LinearRegression::from(graph.parent_weights(&v), graph.intercept(&v), noise);
+            fcm.mechanisms.insert(v, Box::new(mech));
+        }
+        fcm
+    }
+
     pub fn rule<M: Mechanism + 'static>(mut self, target: &str, mech: M) -> Self {
         self.mechanisms.insert(Variable::from(target), Box::new(mech));
         self
@@ -103,7 +121,7 @@ impl FCM {
                 // Check for Intervention
                 if let Some(forced_value) = intervention_map.get(node) {
                     // "Graph Surgery": Force value, ignore parents
-                    data_store.get_mut(node).unwrap().push(forced_value.clone());
+                    data_store.get_mut(node).unwrap().push(*forced_value);
                 } else {
                     // Standard Logic
                     let mut parents = self.graph.get_parents(node);
@@ -111,7 +129,7 @@ impl FCM {
 
                     let parent_values: Vec<Value> = parents.iter()
                         .map(|p_name| {
-                            data_store.get(p_name).unwrap().last().unwrap().clone()
+                            *data_store.get(p_name).unwrap().last().unwrap()
                         })
                         .collect();
 
@@ -130,11 +148,8 @@ impl FCM {
         let columns: Vec<Column> = data_store
             .into_iter()
             .map(|(var_name, values)| {
-                // Unwrap Vec<Value> into Vec<f64>
-                let raw_values: Vec<f64> = values.into_iter().map(|v| v).collect();
-
                 // Create Series and convert it to Column using .into()
-                Series::new(PlSmallStr::from(var_name), raw_values).into()
+                Series::new(PlSmallStr::from(var_name), values).into()
             })
             .collect();
 
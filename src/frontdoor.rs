@@ -0,0 +1,138 @@
+use std::collections::{HashMap, HashSet};
+use ndarray::Array1;
+use petgraph::Direction;
+use polars::prelude::DataFrame;
+use crate::backdoor::backdoor_graph;
+use crate::dag::{DagError, Variable, DAG};
+use crate::mechanism::{column_to_array, columns_to_array, LinearRegression};
+use crate::pc::get_combinations;
+
+/// Checks Pearl's front-door criterion for `mediators` relative to `treatment` -> `outcome`:
+/// 1. the mediators intercept every directed path from treatment to outcome,
+/// 2. there is no unblocked backdoor path from the treatment to the mediators,
+/// 3. every backdoor path from the mediators to the outcome is blocked by the treatment.
+pub fn is_frontdoor_set(
+    dag: &DAG,
+    treatment: &Variable,
+    outcome: &Variable,
+    mediators: &[Variable],
+) -> Result<bool, DagError> {
+    for v in [treatment, outcome].into_iter().chain(mediators) {
+        dag.get_index(v).ok_or_else(|| DagError::UnknownVariable(v.clone()))?;
+    }
+    if mediators.is_empty() || mediators.contains(treatment) || mediators.contains(outcome) {
+        return Ok(false);
+    }
+
+    // (1) No directed path treatment -> outcome avoiding the mediators
+    if directed_path_avoiding(dag, treatment, outcome, mediators) {
+        return Ok(false);
+    }
+
+    // (2) Treatment and mediators are d-separated once the treatment's own effects are cut
+    let cut_treatment = backdoor_graph(dag, std::slice::from_ref(treatment));
+    if !cut_treatment.is_d_separated(std::slice::from_ref(treatment), mediators, &[])? {
+        return Ok(false);
+    }
+
+    // (3) The treatment blocks all backdoor paths from the mediators to the outcome
+    let cut_mediators = backdoor_graph(dag, mediators);
+    cut_mediators.is_d_separated(mediators, std::slice::from_ref(outcome), std::slice::from_ref(treatment))
+}
+
+/// Searches for the smallest set of observed mediators satisfying the front-door criterion.
+///
+/// Unobserved confounders are represented as ordinary nodes of the DAG and listed in `latent`;
+/// they are never proposed as mediators. Only nodes on causal paths from the treatment to the
+/// outcome are considered, and ties are broken alphabetically.
+pub fn find_frontdoor_set(
+    dag: &DAG,
+    treatment: &Variable,
+    outcome: &Variable,
+    latent: &[Variable],
+) -> Result<Option<Vec<Variable>>, DagError> {
+    let descendants = dag.descendants(std::slice::from_ref(treatment))?;
+    let ancestors = dag.ancestors(std::slice::from_ref(outcome))?;
+
+    let mut pool: Vec<Variable> = descendants.intersection(&ancestors)
+        .filter(|v| *v != treatment && *v != outcome && !latent.contains(v))
+        .cloned()
+        .collect();
+    pool.sort();
+
+    for size in 1..=pool.len() {
+        for candidate in get_combinations(&pool, size) {
+            if is_frontdoor_set(dag, treatment, outcome, &candidate)? {
+                return Ok(Some(candidate));
+            }
+        }
+    }
+    Ok(None)
+}
+
+#[derive(Debug, Clone)]
+pub struct FrontDoorEstimate {
+    /// Average change in the outcome per unit change in the treatment
+    pub effect: f64,
+    /// Effect of the treatment on each mediator (regression of the mediator on the treatment)
+    pub treatment_to_mediator: HashMap<Variable, f64>,
+    /// Effect of each mediator on the outcome, adjusting for the treatment and the other mediators
+    pub mediator_to_outcome: HashMap<Variable, f64>,
+}
+
+/// Front-door estimate of the effect of `treatment` on `outcome` for a linear model.
+///
+/// Each mediator is regressed on the treatment alone, then the outcome is regressed on the
+/// mediators plus the treatment (which blocks the mediator -> outcome backdoor paths).
+/// The effect is the sum over mediators of the product of the two coefficients.
+/// `mediators` should satisfy the front-door criterion, see [`find_frontdoor_set`].
+pub fn estimate_effect(
+    df: &DataFrame,
+    treatment: &Variable,
+    outcome: &Variable,
+    mediators: &[Variable],
+) -> FrontDoorEstimate {
+    let mut treatment_to_mediator = HashMap::new();
+    for m in mediators {
+        let mut lr = LinearRegression::new();
+        lr.fit_ndarray(columns_to_array(df, std::slice::from_ref(treatment)), &column_to_array(df, m));
+        treatment_to_mediator.insert(m.clone(), lr.weights().expect("Model was not fit")[0]);
+    }
+
+    let mut regressors: Vec<Variable> = mediators.to_vec();
+    regressors.push(treatment.clone());
+    let mut lr = LinearRegression::new();
+    lr.fit_ndarray(columns_to_array(df, &regressors), &column_to_array(df, outcome));
+    let weights: &Array1<f64> = lr.weights().expect("Model was not fit");
+
+    let mediator_to_outcome: HashMap<Variable, f64> = mediators.iter()
+        .enumerate()
+        .map(|(i, m)| (m.clone(), weights[i]))
+        .collect();
+
+    let effect = mediators.iter()
+        .map(|m| treatment_to_mediator[m] * mediator_to_outcome[m])
+        .sum();
+
+    FrontDoorEstimate { effect, treatment_to_mediator, mediator_to_outcome }
+}
+
+// Is there a directed path from `from` to `to` that never enters `blocked`?
+fn directed_path_avoiding(dag: &DAG, from: &Variable, to: &Variable, blocked: &[Variable]) -> bool {
+    let start = dag.get_index(from).expect("Node not found");
+    let target = dag.get_index(to).expect("Node not found");
+    let blocked: HashSet<_> = blocked.iter().filter_map(|v| dag.get_index(v)).collect();
+
+    let mut seen = HashSet::new();
+    let mut stack = vec![start];
+    while let Some(node) = stack.pop() {
+        if node == target { return true; }
+        if !seen.insert(node) { continue; }
+        for child in dag.graph.neighbors_directed(node, Direction::Outgoing) {
+            if !blocked.contains(&child) {
+                stack.push(child);
+            }
+        }
+    }
+    false
+}
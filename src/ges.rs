@@ -0,0 +1,266 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use ndarray::{Array1, Array2, Axis};
+use polars::prelude::DataFrame;
+use log::info;
+use crate::dag::Variable;
+use crate::linalg::invert;
+use crate::mechanism::column_to_array;
+use crate::pc::get_combinations;
+use crate::pdag::PDAG;
+
+/// Greedy Equivalence Search (Chickering, 2002): a score-based search over CPDAGs.
+///
+/// Starts from the empty graph, greedily inserts the edges that most improve the BIC score
+/// (forward phase), then greedily deletes edges while that improves it (backward phase).
+/// The score assumes linear-Gaussian data.
+pub struct GES {
+    /// Learned CPDAG, the same representation as the output of PC
+    pub graph: PDAG,
+    score: BicScore,
+}
+
+/// A candidate Insert(X, Y, T) or Delete(X, Y, H) and its score improvement
+struct Operator {
+    x: Variable,
+    y: Variable,
+    set: Vec<Variable>,
+    delta: f64,
+}
+
+impl GES {
+    pub fn new(variables: Vec<Variable>, data: &DataFrame) -> Self {
+        let mut graph = PDAG::new();
+        for v in &variables {
+            graph.add_node(v.clone());
+        }
+        GES { graph, score: BicScore::new(&variables, data) }
+    }
+
+    /// Multiplies the BIC complexity penalty; values above 1 give sparser graphs (default 1)
+    pub fn penalty_discount(mut self, penalty: f64) -> Self {
+        assert!(penalty > 0.0, "Penalty discount must be positive, got {}", penalty);
+        self.score.penalty = penalty;
+        self
+    }
+
+    /// Run both phases, starting from the empty graph
+    pub fn run(&mut self) {
+        info!("Phase 1: Forward Equivalence Search...");
+        while let Some(op) = self.best_insert() {
+            info!("GES Insert: {} -> {} with T = {:?} (score +{:.3})", op.x, op.y, op.set, op.delta);
+            self.graph.add_directed(&op.x, &op.y);
+            for t in &op.set {
+                self.graph.orient(t, &op.y);
+            }
+            self.rebuild_cpdag();
+        }
+
+        info!("Phase 2: Backward Equivalence Search...");
+        while let Some(op) = self.best_delete() {
+            info!("GES Delete: {} - {} with H = {:?} (score +{:.3})", op.x, op.y, op.set, op.delta);
+            self.graph.remove_edge(&op.x, &op.y);
+            for h in &op.set {
+                self.graph.orient(&op.y, h);
+                if self.graph.is_undirected(&op.x, h) {
+                    self.graph.orient(&op.x, h);
+                }
+            }
+            self.rebuild_cpdag();
+        }
+    }
+
+    /// BIC score of the current graph (higher is better)
+    pub fn score(&mut self) -> f64 {
+        let dag = self.graph.consistent_extension().expect("Graph has no consistent extension");
+        dag.variables().iter()
+            .map(|v| self.score.local(v, &dag.get_parents(v)))
+            .sum()
+    }
+
+    // ==========================================
+    // Forward Phase
+    // ==========================================
+    fn best_insert(&mut self) -> Option<Operator> {
+        let mut best: Option<Operator> = None;
+        let variables = self.sorted_variables();
+
+        for x in &variables {
+            for y in &variables {
+                if x == y || self.graph.is_adjacent(x, y) { continue; }
+
+                let na = self.na(y, x);
+                let t0: Vec<Variable> = self.sorted(self.graph.undirected_neighbors(y)).into_iter()
+                    .filter(|t| !self.graph.is_adjacent(t, x))
+                    .collect();
+
+                for size in 0..=t0.len() {
+                    for t in get_combinations(&t0, size) {
+                        let mut na_t = na.clone();
+                        na_t.extend(t.iter().cloned());
+                        if !self.is_clique(&na_t) || !self.blocks_semi_directed_paths(y, x, &na_t) { continue; }
+
+                        let mut parents = na_t;
+                        parents.extend(self.graph.get_parents(y));
+                        let without = self.score.local(y, &parents);
+                        parents.push(x.clone());
+                        let delta = self.score.local(y, &parents) - without;
+
+                        if delta > 0.0 && best.as_ref().is_none_or(|b| delta > b.delta) {
+                            best = Some(Operator { x: x.clone(), y: y.clone(), set: t, delta });
+                        }
+                    }
+                }
+            }
+        }
+        best
+    }
+
+    // Every semi-directed path from Y to X goes through `blocked`
+    fn blocks_semi_directed_paths(&self, y: &str, x: &str, blocked: &[Variable]) -> bool {
+        let mut visited: HashSet<Variable> = blocked.iter().cloned().collect();
+        visited.insert(y.to_string());
+        let mut queue = VecDeque::from([y.to_string()]);
+
+        while let Some(node) = queue.pop_front() {
+            let next = self.graph.get_children(&node).into_iter()
+                .chain(self.graph.undirected_neighbors(&node));
+            for n in next {
+                if n == x { return false; }
+                if visited.insert(n.clone()) {
+                    queue.push_back(n);
+                }
+            }
+        }
+        true
+    }
+
+    // ==========================================
+    // Backward Phase
+    // ==========================================
+    fn best_delete(&mut self) -> Option<Operator> {
+        let mut best: Option<Operator> = None;
+        let variables = self.sorted_variables();
+
+        for x in &variables {
+            for y in &variables {
+                // X -> Y, or X - Y (both orders are considered)
+                if !(self.graph.is_directed(x, y) || self.graph.is_undirected(x, y)) { continue; }
+
+                let h0 = self.na(y, x);
+                for size in 0..=h0.len() {
+                    for h in get_combinations(&h0, size) {
+                        let remaining: Vec<Variable> = h0.iter().filter(|v| !h.contains(v)).cloned().collect();
+                        if !self.is_clique(&remaining) { continue; }
+
+                        let mut parents = remaining;
+                        parents.extend(self.graph.get_parents(y).into_iter().filter(|p| p != x));
+                        let without = self.score.local(y, &parents);
+                        parents.push(x.clone());
+                        let delta = without - self.score.local(y, &parents);
+
+                        if delta > 0.0 && best.as_ref().is_none_or(|b| delta > b.delta) {
+                            best = Some(Operator { x: x.clone(), y: y.clone(), set: h, delta });
+                        }
+                    }
+                }
+            }
+        }
+        best
+    }
+
+    // ==========================================
+    // Graph Helpers
+    // ==========================================
+
+    // NA_{Y,X}: undirected neighbours of Y that are adjacent to X
+    fn na(&self, y: &str, x: &str) -> Vec<Variable> {
+        self.sorted(self.graph.undirected_neighbors(y)).into_iter()
+            .filter(|n| self.graph.is_adjacent(n, x))
+            .collect()
+    }
+
+    fn is_clique(&self, nodes: &[Variable]) -> bool {
+        nodes.iter().enumerate().all(|(i, a)| nodes[i + 1..].iter().all(|b| self.graph.is_adjacent(a, b)))
+    }
+
+    // Back to the CPDAG of the equivalence class after an operator was applied
+    fn rebuild_cpdag(&mut self) {
+        let dag = self.graph.consistent_extension().expect("Operator left a graph with no consistent extension");
+        self.graph = PDAG::from_dag(&dag);
+    }
+
+    fn sorted_variables(&self) -> Vec<Variable> {
+        self.sorted(self.graph.variables())
+    }
+
+    fn sorted(&self, mut variables: Vec<Variable>) -> Vec<Variable> {
+        variables.sort();
+        variables
+    }
+}
+
+/// Decomposable linear-Gaussian BIC, computed from the sample covariance matrix:
+/// score(Y | Pa) = -n ln(residual variance) - penalty * |Pa| * ln(n)
+struct BicScore {
+    index: HashMap<Variable, usize>,
+    covariance: Array2<f64>,
+    n_samples: usize,
+    penalty: f64,
+    cache: HashMap<(usize, Vec<usize>), f64>,
+}
+
+impl BicScore {
+    fn new(variables: &[Variable], data: &DataFrame) -> Self {
+        let n = data.height();
+        let mut x = Array2::<f64>::zeros((n, variables.len()));
+        for (j, v) in variables.iter().enumerate() {
+            let column = column_to_array(data, v);
+            let mean = column.mean().unwrap_or(0.0);
+            x.column_mut(j).assign(&(column - mean));
+        }
+        let covariance = x.t().dot(&x) / n as f64;
+
+        BicScore {
+            index: variables.iter().enumerate().map(|(i, v)| (v.clone(), i)).collect(),
+            covariance,
+            n_samples: n,
+            penalty: 1.0,
+            cache: HashMap::new(),
+        }
+    }
+
+    fn local(&mut self, y: &str, parents: &[Variable]) -> f64 {
+        let position = |name: &str| *self.index.get(name).unwrap_or_else(|| panic!("Unknown variable {}", name));
+        let i = position(y);
+        let mut key: Vec<usize> = parents.iter().map(|p| position(p)).collect();
+        key.sort_unstable();
+        key.dedup();
+
+        if let Some(score) = self.cache.get(&(i, key.clone())) {
+            return *score;
+        }
+
+        // Residual variance of Y regressed on its parents
+        let mut variance = self.covariance[[i, i]];
+        if !key.is_empty() {
+            let sigma_pp = self.covariance.select(Axis(0), &key).select(Axis(1), &key);
+            let sigma_py: Array1<f64> = key.iter().map(|&p| self.covariance[[p, i]]).collect();
+            variance = match invert(&sigma_pp) {
+                Some(inv) => variance - sigma_py.dot(&inv.dot(&sigma_py)),
+                // Collinear parents never help
+                None => f64::NAN,
+            };
+        }
+
+        let n = self.n_samples as f64;
+        let score = if variance.is_nan() {
+            f64::NEG_INFINITY
+        } else {
+            // An exact fit would otherwise give an infinite score
+            let variance = variance.max(f64::EPSILON * self.covariance[[i, i]]);
+            -n * variance.ln() - self.penalty * key.len() as f64 * n.ln()
+        };
+        self.cache.insert((i, key), score);
+        score
+    }
+}
@@ -0,0 +1,372 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+use crate::admg::ADMG;
+use crate::dag::{DagError, Variable, DAG};
+
+pub type VarSet = BTreeSet<Variable>;
+
+/// A symbolic expression over (conditional) observational distributions
+#[derive(Debug, Clone, PartialEq)]
+pub enum Estimand {
+    /// P(vars | given)
+    Prob { vars: VarSet, given: VarSet },
+    /// Sum of `expr` over all values of `over`
+    Sum { over: VarSet, expr: Box<Estimand> },
+    Product(Vec<Estimand>),
+    Fraction { numerator: Box<Estimand>, denominator: Box<Estimand> },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum IdError {
+    Graph(DagError),
+    /// The effect is not identifiable; the two node sets form a hedge (F, F')
+    NotIdentifiable { hedge: (VarSet, VarSet) },
+}
+
+impl fmt::Display for IdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IdError::Graph(e) => write!(f, "{}", e),
+            IdError::NotIdentifiable { hedge: (big, small) } => write!(
+                f,
+                "effect is not identifiable: hedge formed by {{{}}} and {{{}}}",
+                join(big, ", "),
+                join(small, ", ")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for IdError {}
+
+impl From<DagError> for IdError {
+    fn from(e: DagError) -> Self {
+        IdError::Graph(e)
+    }
+}
+
+/// Identifies P(outcome | do(treatment)) from the observational distribution over the observed
+/// variables, using the ID algorithm of Shpitser & Pearl (2006).
+///
+/// Unobserved confounders are ordinary nodes of `dag` listed in `latent`; they are projected out
+/// into bidirected edges (see [`ADMG::latent_projection`]) before running [`identify_admg`].
+pub fn identify(
+    dag: &DAG,
+    latent: &[Variable],
+    outcome: &[Variable],
+    treatment: &[Variable],
+) -> Result<Estimand, IdError> {
+    for v in outcome.iter().chain(treatment) {
+        if latent.contains(v) {
+            return Err(DagError::OverlappingSets(v.clone()).into());
+        }
+    }
+    let admg = ADMG::latent_projection(dag, latent)?;
+    identify_admg(&admg, outcome, treatment)
+}
+
+/// Identifies P(outcome | do(treatment)) in an ADMG, where bidirected edges stand for hidden
+/// confounding. Returns the estimand, or the hedge that proves the effect is not identifiable.
+///
+/// Note that, as in the original algorithm, a variable can appear both as a free variable and as
+/// a summation index in different parts of the estimand (e.g. the front-door formula).
+pub fn identify_admg(
+    admg: &ADMG,
+    outcome: &[Variable],
+    treatment: &[Variable],
+) -> Result<Estimand, IdError> {
+    for v in outcome.iter().chain(treatment) {
+        admg.get_index(v).ok_or_else(|| DagError::UnknownVariable(v.clone()))?;
+    }
+    if let Some(v) = outcome.iter().find(|v| treatment.contains(v)) {
+        return Err(DagError::OverlappingSets(v.clone()).into());
+    }
+
+    let graph = Projection::new(admg);
+    let all: VarSet = graph.order.iter().cloned().collect();
+    let p = Distribution::observational(all.clone());
+
+    id(&to_set(outcome), &to_set(treatment), p, &graph, &all)
+}
+
+// ==========================================
+// The ID algorithm
+// ==========================================
+fn id(y: &VarSet, x: &VarSet, p: Distribution, g: &Projection, v: &VarSet) -> Result<Estimand, IdError> {
+    // Line 1: no intervention, just marginalise
+    if x.is_empty() {
+        return Ok(p.marginal(y));
+    }
+
+    // Line 2: drop everything that is not an ancestor of Y
+    let an_y = g.ancestors(y, v, &VarSet::new());
+    if an_y != *v {
+        let x_new: VarSet = x.intersection(&an_y).cloned().collect();
+        return id(y, &x_new, p.marginalised_to(&an_y), g, &an_y);
+    }
+
+    // Line 3: intervene on nodes that have no effect on Y once X is fixed
+    let an_y_cut = g.ancestors(y, v, x);
+    let w: VarSet = v.difference(x).filter(|n| !an_y_cut.contains(*n)).cloned().collect();
+    if !w.is_empty() {
+        let x_new: VarSet = x.union(&w).cloned().collect();
+        return id(y, &x_new, p, g, v);
+    }
+
+    // Line 4: factorise over the c-components of G \ X
+    let v_minus_x: VarSet = v.difference(x).cloned().collect();
+    let components = g.c_components(&v_minus_x);
+    if components.len() > 1 {
+        let mut factors = Vec::new();
+        for s in &components {
+            let rest: VarSet = v.difference(s).cloned().collect();
+            factors.push(id(s, &rest, p.clone(), g, v)?);
+        }
+        let over: VarSet = v.iter().filter(|n| !y.contains(*n) && !x.contains(*n)).cloned().collect();
+        return Ok(sum(over, product(factors)));
+    }
+
+    let s = components.into_iter().next().unwrap_or_default();
+    let full_components = g.c_components(v);
+
+    // Line 5: G is a single c-component, so (G, S) is a hedge
+    if full_components.len() == 1 {
+        return Err(IdError::NotIdentifiable { hedge: (v.clone(), s) });
+    }
+
+    // Line 6: S is itself a c-component of G
+    if full_components.contains(&s) {
+        let factors = g.order_within(&s, v).into_iter()
+            .map(|vi| {
+                let pred = g.predecessors(&vi, v);
+                p.conditional(&vi, &pred)
+            })
+            .collect();
+        let over: VarSet = s.difference(y).cloned().collect();
+        return Ok(sum(over, product(factors)));
+    }
+
+    // Line 7: S sits inside a bigger c-component S'; recurse on S' with its own distribution
+    let s_prime = full_components.into_iter()
+        .find(|c| s.is_subset(c))
+        .expect("C-component of G \\ X must lie inside a c-component of G");
+    let factors = g.order_within(&s_prime, v).into_iter()
+        .map(|vi| {
+            let pred = g.predecessors(&vi, v);
+            p.conditional(&vi, &pred)
+        })
+        .collect();
+    let p_new = Distribution { expr: product(factors), vars: s_prime.clone(), base: false };
+    let x_new: VarSet = x.intersection(&s_prime).cloned().collect();
+    id(y, &x_new, p_new, g, &s_prime)
+}
+
+// A joint distribution over `vars`, as an expression of observational quantities
+#[derive(Debug, Clone)]
+struct Distribution {
+    expr: Estimand,
+    vars: VarSet,
+    // true when `expr` is a plain P(vars | ...) that can be marginalised or conditioned directly
+    base: bool,
+}
+
+impl Distribution {
+    fn observational(vars: VarSet) -> Self {
+        Distribution {
+            expr: Estimand::Prob { vars: vars.clone(), given: VarSet::new() },
+            vars,
+            base: true,
+        }
+    }
+
+    // Sum over everything except `keep`
+    fn marginal(&self, keep: &VarSet) -> Estimand {
+        let over: VarSet = self.vars.difference(keep).cloned().collect();
+        if self.base && let Estimand::Prob { given, .. } = &self.expr {
+            let vars = self.vars.intersection(keep).cloned().collect();
+            return Estimand::Prob { vars, given: given.clone() };
+        }
+        sum(over, self.expr.clone())
+    }
+
+    fn marginalised_to(&self, keep: &VarSet) -> Distribution {
+        Distribution {
+            expr: self.marginal(keep),
+            vars: self.vars.intersection(keep).cloned().collect(),
+            base: self.base,
+        }
+    }
+
+    // P(target | given) implied by this distribution
+    fn conditional(&self, target: &Variable, given: &VarSet) -> Estimand {
+        if self.base && let Estimand::Prob { given: fixed, .. } = &self.expr {
+            let mut all_given = given.clone();
+            all_given.extend(fixed.iter().cloned());
+            return Estimand::Prob { vars: to_set(std::slice::from_ref(target)), given: all_given };
+        }
+
+        let given_here: VarSet = given.intersection(&self.vars).cloned().collect();
+        let mut joint = given_here.clone();
+        joint.insert(target.clone());
+        let numerator = self.marginal(&joint);
+        if given_here.is_empty() {
+            return numerator;
+        }
+        Estimand::Fraction {
+            numerator: Box::new(numerator),
+            denominator: Box::new(self.marginal(&given_here)),
+        }
+    }
+}
+
+fn sum(over: VarSet, expr: Estimand) -> Estimand {
+    if over.is_empty() {
+        return expr;
+    }
+    match expr {
+        Estimand::Sum { over: inner, expr } => {
+            Estimand::Sum { over: over.union(&inner).cloned().collect(), expr }
+        },
+        expr => Estimand::Sum { over, expr: Box::new(expr) },
+    }
+}
+
+fn product(factors: Vec<Estimand>) -> Estimand {
+    let mut flat = Vec::new();
+    for f in factors {
+        match f {
+            Estimand::Product(inner) => flat.extend(inner),
+            f => flat.push(f),
+        }
+    }
+    if flat.len() == 1 { flat.pop().unwrap() } else { Estimand::Product(flat) }
+}
+
+// ==========================================
+// Graph queries restricted to node subsets
+// ==========================================
+
+// The ADMG's adjacency, precomputed for the set operations of the algorithm
+struct Projection {
+    order: Vec<Variable>, // topological order of the observed variables
+    parents: HashMap<Variable, VarSet>,
+    siblings: HashMap<Variable, VarSet>,
+}
+
+impl Projection {
+    fn new(admg: &ADMG) -> Self {
+        let order = admg.sort();
+        let parents = order.iter()
+            .map(|v| (v.clone(), admg.get_parents(v).into_iter().collect()))
+            .collect();
+        let siblings = order.iter()
+            .map(|v| (v.clone(), admg.get_siblings(v).into_iter().collect()))
+            .collect();
+        Projection { order, parents, siblings }
+    }
+
+    // Ancestors of `of` (inclusive) in G[within] with the edges into `cut` removed
+    fn ancestors(&self, of: &VarSet, within: &VarSet, cut: &VarSet) -> VarSet {
+        let mut found = VarSet::new();
+        let mut stack: Vec<Variable> = of.iter().cloned().collect();
+        while let Some(n) = stack.pop() {
+            if !within.contains(&n) || !found.insert(n.clone()) { continue; }
+            if cut.contains(&n) { continue; }
+            stack.extend(self.parents[&n].iter().cloned());
+        }
+        found
+    }
+
+    // Districts (bidirected-connected components) of G[within], in topological order
+    fn c_components(&self, within: &VarSet) -> Vec<VarSet> {
+        let mut components = Vec::new();
+        let mut assigned = HashSet::new();
+        for start in self.order.iter().filter(|v| within.contains(*v)) {
+            if assigned.contains(start) { continue; }
+            let mut component = VarSet::new();
+            let mut stack = vec![start.clone()];
+            while let Some(n) = stack.pop() {
+                if !component.insert(n.clone()) { continue; }
+                assigned.insert(n.clone());
+                stack.extend(self.siblings[&n].iter().filter(|s| within.contains(*s)).cloned());
+            }
+            components.push(component);
+        }
+        components
+    }
+
+    fn order_within(&self, nodes: &VarSet, within: &VarSet) -> Vec<Variable> {
+        self.order.iter().filter(|v| nodes.contains(*v) && within.contains(*v)).cloned().collect()
+    }
+
+    // Nodes of `within` before `node` in the topological order
+    fn predecessors(&self, node: &Variable, within: &VarSet) -> VarSet {
+        self.order.iter()
+            .take_while(|v| *v != node)
+            .filter(|v| within.contains(*v))
+            .cloned()
+            .collect()
+    }
+}
+
+// ==========================================
+// Printing
+// ==========================================
+impl Estimand {
+    /// Renders the estimand as LaTeX (without surrounding `$`)
+    pub fn to_latex(&self) -> String {
+        match self {
+            Estimand::Prob { vars, given } => {
+                if given.is_empty() {
+                    format!("P({})", join(vars, ", "))
+                } else {
+                    format!("P({} \\mid {})", join(vars, ", "), join(given, ", "))
+                }
+            },
+            Estimand::Sum { over, expr } => format!("\\sum_{{{}}} {}", join(over, ", "), expr.to_latex()),
+            Estimand::Product(factors) => factors.iter()
+                .map(|f| match f {
+                    Estimand::Sum { .. } => format!("\\left[{}\\right]", f.to_latex()),
+                    _ => f.to_latex(),
+                })
+                .collect::<Vec<_>>()
+                .join(" "),
+            Estimand::Fraction { numerator, denominator } => {
+                format!("\\frac{{{}}}{{{}}}", numerator.to_latex(), denominator.to_latex())
+            },
+        }
+    }
+}
+
+impl fmt::Display for Estimand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Estimand::Prob { vars, given } => {
+                if given.is_empty() {
+                    write!(f, "P({})", join(vars, ", "))
+                } else {
+                    write!(f, "P({} | {})", join(vars, ", "), join(given, ", "))
+                }
+            },
+            Estimand::Sum { over, expr } => write!(f, "Σ_{{{}}} {}", join(over, ", "), expr),
+            Estimand::Product(factors) => {
+                let parts: Vec<String> = factors.iter()
+                    .map(|factor| match factor {
+                        Estimand::Sum { .. } | Estimand::Fraction { .. } => format!("[{}]", factor),
+                        _ => factor.to_string(),
+                    })
+                    .collect();
+                write!(f, "{}", parts.join(" "))
+            },
+            Estimand::Fraction { numerator, denominator } => write!(f, "[{}] / [{}]", numerator, denominator),
+        }
+    }
+}
+
+fn join(set: &VarSet, sep: &str) -> String {
+    set.iter().cloned().collect::<Vec<_>>().join(sep)
+}
+
+fn to_set(vars: &[Variable]) -> VarSet {
+    vars.iter().cloned().collect()
+}
@@ -0,0 +1,466 @@
+use std::collections::{BTreeMap, HashMap};
+use std::f64::consts::SQRT_2;
+use std::sync::Mutex;
+use ndarray::{concatenate, Array1, Array2, Axis};
+use polars::prelude::{DataFrame, DataType};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use crate::dag::Variable;
+use crate::linalg::invert;
+use crate::mechanism::column_to_array;
+use crate::stats::{chi2_sf, erfc, gamma_q};
+
+/// Outcome of a conditional independence test
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CITestResult {
+    pub statistic: f64,
+    pub p_value: f64,
+}
+
+/// A conditional independence test of X _||_ Y | Z.
+///
+/// The null hypothesis is independence: a small p-value is evidence that X and Y are dependent
+/// given Z. Implement this to plug a custom test into [`crate::pc::PC`]. Tests must be thread
+/// safe so the skeleton search can run them in parallel (the `parallel` feature). The bound holds
+/// whether or not the feature is on, so turning it on never breaks an implementation.
+pub trait CITest: Send + Sync {
+    fn test(&self, x: &str, y: &str, z: &[Variable]) -> CITestResult;
+}
+
+/// Fisher z-test on partial correlations, for (roughly) Gaussian continuous data
+pub struct FisherZ {
+    index: HashMap<Variable, usize>,
+    correlation: Array2<f64>,
+    n_samples: usize,
+    /// Inverse of the correlation submatrix over each index set tested so far (None if singular)
+    precision_cache: Mutex<HashMap<Vec<usize>, Option<Array2<f64>>>>,
+    cache_capacity: usize,
+}
+
+// Default number of cached inverses
+const CACHE_CAPACITY: usize = 10_000;
+
+impl FisherZ {
+    /// Precomputes the Pearson correlation of every pair of columns; numeric columns of any type
+    /// are cast to f64.
+    pub fn new(data: &DataFrame) -> Self {
+        let names: Vec<Variable> = data.get_column_names().iter().map(|n| n.to_string()).collect();
+        let columns: Vec<Array1<f64>> = names.iter().map(|n| column_to_array(data, n)).collect();
+
+        let mut correlation = Array2::<f64>::eye(names.len());
+        for i in 0..names.len() {
+            for j in (i + 1)..names.len() {
+                let corr = pearson_corr(&columns[i], &columns[j]);
+                correlation[[i, j]] = corr;
+                correlation[[j, i]] = corr;
+            }
+        }
+
+        Self::build(names, correlation, data.height())
+    }
+
+    /// From sufficient statistics: the correlation matrix of `variables` (rows and columns in the
+    /// same order) estimated from `n_samples` observations
+    pub fn from_correlation(variables: Vec<Variable>, correlation: Array2<f64>, n_samples: usize) -> Self {
+        let n = variables.len();
+        assert_eq!(correlation.dim(), (n, n), "Correlation matrix must be {} x {}", n, n);
+        for i in 0..n {
+            assert!((correlation[[i, i]] - 1.0).abs() < 1e-8, "Correlation matrix must have a unit diagonal");
+            for j in (i + 1)..n {
+                assert!((correlation[[i, j]] - correlation[[j, i]]).abs() < 1e-8, "Correlation matrix must be symmetric");
+            }
+        }
+        Self::build(variables, correlation, n_samples)
+    }
+
+    /// From sufficient statistics: the covariance matrix of `variables` (rows and columns in the
+    /// same order) estimated from `n_samples` observations
+    pub fn from_covariance(variables: Vec<Variable>, covariance: Array2<f64>, n_samples: usize) -> Self {
+        let n = variables.len();
+        assert_eq!(covariance.dim(), (n, n), "Covariance matrix must be {} x {}", n, n);
+        assert!(covariance.diag().iter().all(|&v| v > 0.0), "Covariance matrix must have a positive diagonal");
+
+        let std = covariance.diag().mapv(f64::sqrt);
+        let mut correlation = Array2::<f64>::eye(n);
+        for i in 0..n {
+            for j in (i + 1)..n {
+                let corr = covariance[[i, j]] / (std[i] * std[j]);
+                assert!((corr - covariance[[j, i]] / (std[i] * std[j])).abs() < 1e-8, "Covariance matrix must be symmetric");
+                correlation[[i, j]] = corr;
+                correlation[[j, i]] = corr;
+            }
+        }
+        Self::build(variables, correlation, n_samples)
+    }
+
+    fn build(variables: Vec<Variable>, correlation: Array2<f64>, n_samples: usize) -> Self {
+        FisherZ {
+            index: variables.into_iter().enumerate().map(|(i, name)| (name, i)).collect(),
+            correlation,
+            n_samples,
+            precision_cache: Mutex::new(HashMap::new()),
+            cache_capacity: CACHE_CAPACITY,
+        }
+    }
+
+    /// Maximum number of submatrix inverses kept between tests (default 10,000); the cache is
+    /// emptied when it fills up, and 0 disables it. Conditioning on k variables stores a
+    /// (k + 2) x (k + 2) matrix of f64, so the default costs about 2 MB at depth 3.
+    pub fn cache_capacity(mut self, capacity: usize) -> Self {
+        self.cache_capacity = capacity;
+        self
+    }
+
+    /// Partial correlation rho_xy|z, read off the inverse P of the correlation submatrix over
+    /// {x, y} and Z: rho = -P_xy / sqrt(P_xx P_yy)
+    fn partial_correlation(&self, x: &str, y: &str, z: &[Variable]) -> f64 {
+        let position = |name: &str| *self.index.get(name).unwrap_or_else(|| panic!("Unknown variable {}", name));
+        let (i, j) = (position(x), position(y));
+        if z.is_empty() {
+            return self.correlation[[i, j]];
+        }
+
+        // Sorted, so that every test over the same variables shares one inverse
+        let mut key: Vec<usize> = z.iter().map(|v| position(v)).collect();
+        key.extend([i, j]);
+        key.sort_unstable();
+        key.dedup();
+
+        // The guard is a temporary, so the lock is released at the end of this statement and the
+        // inversion below runs unlocked; parallel tests only contend for the lookup and insert.
+        // Two threads may both miss on the same key and invert it twice, which is harmless.
+        let cached = self.precision_cache.lock().unwrap().get(&key).cloned();
+        let precision = cached.unwrap_or_else(|| {
+            let sub = self.correlation.select(Axis(0), &key).select(Axis(1), &key);
+            let precision = invert(&sub);
+            if self.cache_capacity > 0 {
+                let mut cache = self.precision_cache.lock().unwrap();
+                if cache.len() >= self.cache_capacity {
+                    cache.clear();
+                }
+                cache.insert(key.clone(), precision.clone());
+            }
+            precision
+        });
+
+        // A singular submatrix has no well-defined partial correlation
+        let Some(precision) = precision else { return 0.0; };
+        let (a, b) = (key.binary_search(&i).unwrap(), key.binary_search(&j).unwrap());
+        let den = (precision[[a, a]] * precision[[b, b]]).sqrt();
+        if den == 0.0 { 0.0 } else { -precision[[a, b]] / den }
+    }
+}
+
+impl CITest for FisherZ {
+    fn test(&self, x: &str, y: &str, z: &[Variable]) -> CITestResult {
+        // 1. Calculate Partial Correlation
+        let r = self.partial_correlation(x, y, z);
+
+        // 2. Fisher Z-Transform
+        // Correlation of 1 means dependent
+        if r.abs() >= 1.0 { return CITestResult { statistic: f64::INFINITY, p_value: 0.0 }; }
+        let z_stat = 0.5 * ((1.0 + r) / (1.0 - r)).ln();
+
+        // 3. Z-Test Statistic
+        // Degrees of freedom = N - |Z| - 3
+        let n = self.n_samples as f64;
+        let k = z.len() as f64;
+        let scale = (n - k - 3.0).sqrt();
+        let statistic = (scale * z_stat).abs();
+
+        // 4. Two-sided p-value under the standard normal
+        let p_value = erfc(statistic / SQRT_2);
+
+        CITestResult { statistic, p_value }
+    }
+}
+
+/// G² (likelihood-ratio) test for discrete data, stratified over the conditioning set
+pub struct GSquare {
+    codes: HashMap<Variable, Vec<usize>>,
+}
+
+impl GSquare {
+    /// Every column is treated as categorical: integers, booleans and strings all work, each
+    /// distinct value (including null) being its own category.
+    pub fn new(data: &DataFrame) -> Self {
+        GSquare { codes: encode_columns(data) }
+    }
+}
+
+impl CITest for GSquare {
+    fn test(&self, x: &str, y: &str, z: &[Variable]) -> CITestResult {
+        stratified_test(&self.codes, x, y, z, |observed, expected| {
+            if observed > 0.0 { 2.0 * observed * (observed / expected).ln() } else { 0.0 }
+        })
+    }
+}
+
+/// Pearson chi-square test for discrete data, stratified over the conditioning set
+pub struct ChiSquare {
+    codes: HashMap<Variable, Vec<usize>>,
+}
+
+impl ChiSquare {
+    /// Every column is treated as categorical, see [`GSquare::new`]
+    pub fn new(data: &DataFrame) -> Self {
+        ChiSquare { codes: encode_columns(data) }
+    }
+}
+
+impl CITest for ChiSquare {
+    fn test(&self, x: &str, y: &str, z: &[Variable]) -> CITestResult {
+        stratified_test(&self.codes, x, y, z, |observed, expected| {
+            (observed - expected).powi(2) / expected
+        })
+    }
+}
+
+// Sums `cell(observed, expected)` over the X-Y contingency table of every stratum of Z.
+// Each stratum contributes (r - 1)(c - 1) degrees of freedom, counting only the rows and columns
+// that actually occur in it, so sparse strata don't inflate the degrees of freedom.
+fn stratified_test<F>(codes: &HashMap<Variable, Vec<usize>>, x: &str, y: &str, z: &[Variable], cell: F) -> CITestResult
+where
+    F: Fn(f64, f64) -> f64,
+{
+    let column = |name: &str| codes.get(name).unwrap_or_else(|| panic!("Unknown variable {}", name));
+    let (xs, ys) = (column(x), column(y));
+    let zs: Vec<&Vec<usize>> = z.iter().map(|v| column(v)).collect();
+
+    let mut strata: BTreeMap<Vec<usize>, BTreeMap<(usize, usize), f64>> = BTreeMap::new();
+    for row in 0..xs.len() {
+        let key: Vec<usize> = zs.iter().map(|col| col[row]).collect();
+        *strata.entry(key).or_default().entry((xs[row], ys[row])).or_insert(0.0) += 1.0;
+    }
+
+    let mut statistic = 0.0;
+    let mut df = 0;
+    for table in strata.values() {
+        let mut row_totals: BTreeMap<usize, f64> = BTreeMap::new();
+        let mut col_totals: BTreeMap<usize, f64> = BTreeMap::new();
+        for (&(i, j), &count) in table {
+            *row_totals.entry(i).or_insert(0.0) += count;
+            *col_totals.entry(j).or_insert(0.0) += count;
+        }
+        let n: f64 = row_totals.values().sum();
+
+        for (i, row_total) in &row_totals {
+            for (j, col_total) in &col_totals {
+                let observed = table.get(&(*i, *j)).copied().unwrap_or(0.0);
+                statistic += cell(observed, row_total * col_total / n);
+            }
+        }
+        df += (row_totals.len() - 1) * (col_totals.len() - 1);
+    }
+
+    // No stratum can show any dependence
+    if df == 0 {
+        return CITestResult { statistic: 0.0, p_value: 1.0 };
+    }
+    CITestResult { statistic, p_value: chi2_sf(statistic, df as f64) }
+}
+
+/// How the null distribution of the kernel statistic is obtained
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KernelNull {
+    /// Gamma distribution matching the mean and variance of the statistic under the null (fast)
+    Gamma,
+    /// Empirical distribution of the statistic over random permutations of Y
+    Permutation { n_permutations: usize, seed: u64 },
+}
+
+/// Kernel-based conditional independence test (KCI, Zhang et al. 2011); without a conditioning
+/// set it reduces to HSIC. Detects nonlinear dependence, using Gaussian kernels on standardized
+/// columns with the median heuristic for the bandwidth.
+///
+/// Every test builds and inverts n x n kernel matrices, so the cost grows as O(n^3) in the
+/// number of samples; a few hundred rows is usually enough.
+pub struct KCI {
+    data: HashMap<Variable, Array1<f64>>,
+    null: KernelNull,
+    /// Ridge regularisation of the regression on Z
+    epsilon: f64,
+}
+
+impl KCI {
+    pub fn new(data: &DataFrame) -> Self {
+        let data = data.get_column_names().iter()
+            .map(|name| {
+                let column = column_to_array(data, name);
+                let mean = column.mean().unwrap_or(0.0);
+                let std = column.std(0.0);
+                let scaled = if std > 0.0 { (column - mean) / std } else { column - mean };
+                (name.to_string(), scaled)
+            })
+            .collect();
+        KCI { data, null: KernelNull::Gamma, epsilon: 1e-3 }
+    }
+
+    /// Use a permutation null instead of the gamma approximation
+    pub fn permutations(mut self, n_permutations: usize, seed: u64) -> Self {
+        self.null = KernelNull::Permutation { n_permutations, seed };
+        self
+    }
+
+    // Samples as rows, the given variables as columns
+    fn features(&self, names: &[&str]) -> Array2<f64> {
+        let n = self.data.values().next().map_or(0, |c| c.len());
+        let mut x = Array2::<f64>::zeros((n, names.len()));
+        for (j, name) in names.iter().enumerate() {
+            let column = self.data.get(*name).unwrap_or_else(|| panic!("Unknown variable {}", name));
+            x.column_mut(j).assign(column);
+        }
+        x
+    }
+}
+
+impl CITest for KCI {
+    fn test(&self, x: &str, y: &str, z: &[Variable]) -> CITestResult {
+        let z: Vec<&str> = z.iter().map(|v| v.as_str()).collect();
+        let ky = center(&gaussian_kernel(&self.features(&[y])));
+
+        if z.is_empty() {
+            // HSIC
+            let kx = center(&gaussian_kernel(&self.features(&[x])));
+            let n = kx.nrows() as f64;
+            let statistic = (&kx * &ky).sum();
+
+            let p_value = match self.null {
+                KernelNull::Gamma => {
+                    let mean = kx.diag().sum() * ky.diag().sum() / n;
+                    let var = 2.0 * (&kx * &kx).sum() * (&ky * &ky).sum() / (n * n);
+                    gamma_p_value(statistic, mean, var)
+                }
+                KernelNull::Permutation { n_permutations, seed } =>
+                    permutation_p_value(&kx, &ky, statistic, n_permutations, seed),
+            };
+            return CITestResult { statistic, p_value };
+        }
+
+        // X is kernelised together with Z, and both kernels are regressed on Z
+        let z_features = self.features(&z);
+        let xz = concatenate![Axis(1), self.features(&[x]), &z_features * 0.5];
+        let kx = center(&gaussian_kernel(&xz));
+        let kz = center(&gaussian_kernel(&z_features));
+
+        let n = kz.nrows();
+        let regularised = &kz + &(Array2::<f64>::eye(n) * self.epsilon);
+        let rz = invert(&regularised).expect("Kernel matrix of Z is singular") * self.epsilon;
+        let kx_r = rz.dot(&kx).dot(&rz);
+        let ky_r = rz.dot(&ky).dot(&rz);
+        let statistic = (&kx_r * &ky_r).sum();
+
+        let p_value = match self.null {
+            KernelNull::Gamma => {
+                // Mean and variance of the weighted chi-square null, from the Hadamard product of
+                // the residual kernels
+                let uu = &kx_r * &ky_r;
+                let mean = uu.diag().sum();
+                let var = 2.0 * (&uu * &uu).sum();
+                gamma_p_value(statistic, mean, var)
+            }
+            // Permuting the residual kernel of Y approximates the null once Z is regressed out
+            KernelNull::Permutation { n_permutations, seed } =>
+                permutation_p_value(&kx_r, &ky_r, statistic, n_permutations, seed),
+        };
+        CITestResult { statistic, p_value }
+    }
+}
+
+// Upper tail of the gamma distribution with the given mean and variance
+fn gamma_p_value(statistic: f64, mean: f64, var: f64) -> f64 {
+    if mean <= 0.0 || var <= 0.0 { return 1.0; }
+    let shape = mean * mean / var;
+    let scale = var / mean;
+    gamma_q(shape, statistic.max(0.0) / scale)
+}
+
+fn permutation_p_value(kx: &Array2<f64>, ky: &Array2<f64>, statistic: f64, n_permutations: usize, seed: u64) -> f64 {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut order: Vec<usize> = (0..ky.nrows()).collect();
+    let mut exceed = 0;
+    for _ in 0..n_permutations {
+        order.shuffle(&mut rng);
+        let permuted = ky.select(Axis(0), &order).select(Axis(1), &order);
+        if (kx * &permuted).sum() >= statistic { exceed += 1; }
+    }
+    (1 + exceed) as f64 / (1 + n_permutations) as f64
+}
+
+// Gaussian kernel exp(-|a - b|^2 / (2 s^2)) with s the median pairwise distance
+fn gaussian_kernel(x: &Array2<f64>) -> Array2<f64> {
+    let n = x.nrows();
+    let mut dist2 = Array2::<f64>::zeros((n, n));
+    for i in 0..n {
+        for j in (i + 1)..n {
+            let d: f64 = x.row(i).iter().zip(x.row(j)).map(|(a, b)| (a - b) * (a - b)).sum();
+            dist2[[i, j]] = d;
+            dist2[[j, i]] = d;
+        }
+    }
+
+    let mut distances: Vec<f64> = Vec::with_capacity(n * n.saturating_sub(1) / 2);
+    for i in 0..n {
+        for j in (i + 1)..n {
+            if dist2[[i, j]] > 0.0 { distances.push(dist2[[i, j]]); }
+        }
+    }
+    distances.sort_by(f64::total_cmp);
+    let median = distances.get(distances.len() / 2).copied().unwrap_or(1.0);
+
+    dist2.mapv(|d| (-d / (2.0 * median)).exp())
+}
+
+// H K H, with H = I - 11'/n the centering matrix
+fn center(k: &Array2<f64>) -> Array2<f64> {
+    let row_means = k.mean_axis(Axis(1)).unwrap();
+    let col_means = k.mean_axis(Axis(0)).unwrap();
+    let mean = k.mean().unwrap();
+    let mut centered = k.clone();
+    for ((i, j), v) in centered.indexed_iter_mut() {
+        *v += mean - row_means[i] - col_means[j];
+    }
+    centered
+}
+
+// --- Utilities ---
+
+// Replaces the values of each column by category codes, in order of first appearance
+fn encode_columns(data: &DataFrame) -> HashMap<Variable, Vec<usize>> {
+    let mut codes = HashMap::new();
+    for column in data.get_columns() {
+        let series = column.as_materialized_series()
+            .cast(&DataType::String)
+            .expect("Column cannot be treated as categorical");
+
+        let mut levels: HashMap<Option<&str>, usize> = HashMap::new();
+        let encoded = series.str().unwrap().into_iter()
+            .map(|value| {
+                let next = levels.len();
+                *levels.entry(value).or_insert(next)
+            })
+            .collect();
+        codes.insert(column.name().to_string(), encoded);
+    }
+    codes
+}
+
+fn pearson_corr(a: &Array1<f64>, b: &Array1<f64>) -> f64 {
+    let mean_a = a.mean().unwrap_or(0.0);
+    let mean_b = b.mean().unwrap_or(0.0);
+
+    let mut num = 0.0;
+    let mut den_a = 0.0;
+    let mut den_b = 0.0;
+
+    for (va, vb) in a.iter().zip(b) {
+        let da = va - mean_a;
+        let db = vb - mean_b;
+        num += da * db;
+        den_a += da * da;
+        den_b += db * db;
+    }
+
+    if den_a == 0.0 || den_b == 0.0 { 0.0 } else { num / (den_a.sqrt() * den_b.sqrt()) }
+}
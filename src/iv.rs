@@ -0,0 +1,134 @@
+use ndarray::{concatenate, Array1, Array2, Axis};
+use polars::prelude::DataFrame;
+use crate::backdoor::backdoor_graph;
+use crate::dag::{DagError, Variable, DAG};
+use crate::linalg::invert;
+use crate::mechanism::{column_to_array, columns_to_array, LinearRegression};
+use crate::pc::get_combinations;
+
+/// An instrument for the effect of a treatment on an outcome, valid once `conditioning` is adjusted for
+#[derive(Debug, Clone, PartialEq)]
+pub struct Instrument {
+    pub instrument: Variable,
+    pub conditioning: Vec<Variable>,
+}
+
+/// Checks whether `instrument` is a (conditional) instrumental variable for `treatment` -> `outcome`
+/// given `conditioning`:
+/// 1. neither the instrument nor the conditioning set contain descendants of the treatment,
+/// 2. the conditioning set d-separates the instrument from the outcome once the treatment's
+///    outgoing edges are removed,
+/// 3. the instrument is still d-connected to the treatment given the conditioning set.
+pub fn is_instrument(
+    dag: &DAG,
+    treatment: &Variable,
+    outcome: &Variable,
+    instrument: &Variable,
+    conditioning: &[Variable],
+) -> Result<bool, DagError> {
+    dag.get_index(outcome).ok_or_else(|| DagError::UnknownVariable(outcome.clone()))?;
+    let descendants = dag.descendants(std::slice::from_ref(treatment))?;
+
+    if descendants.contains(instrument) || conditioning.iter().any(|v| descendants.contains(v)) {
+        return Ok(false);
+    }
+
+    let cut = backdoor_graph(dag, std::slice::from_ref(treatment));
+    if !cut.is_d_separated(std::slice::from_ref(instrument), std::slice::from_ref(outcome), conditioning)? {
+        return Ok(false);
+    }
+
+    Ok(!dag.is_d_separated(std::slice::from_ref(instrument), std::slice::from_ref(treatment), conditioning)?)
+}
+
+/// Finds every observed variable that can serve as an instrument, together with the smallest
+/// conditioning set that makes it valid (ties broken alphabetically).
+///
+/// Unobserved variables are listed in `latent`; they are never used as instruments or conditioned on.
+pub fn find_instruments(
+    dag: &DAG,
+    treatment: &Variable,
+    outcome: &Variable,
+    latent: &[Variable],
+) -> Result<Vec<Instrument>, DagError> {
+    let descendants = dag.descendants(std::slice::from_ref(treatment))?;
+    dag.get_index(outcome).ok_or_else(|| DagError::UnknownVariable(outcome.clone()))?;
+
+    let mut observed: Vec<Variable> = dag.variables()
+        .into_iter()
+        .filter(|v| v != treatment && v != outcome && !latent.contains(v) && !descendants.contains(v))
+        .collect();
+    observed.sort();
+
+    let mut found = Vec::new();
+    for z in &observed {
+        let pool: Vec<Variable> = observed.iter().filter(|v| *v != z).cloned().collect();
+
+        'search: for size in 0..=pool.len() {
+            for conditioning in get_combinations(&pool, size) {
+                if is_instrument(dag, treatment, outcome, z, &conditioning)? {
+                    found.push(Instrument { instrument: z.clone(), conditioning });
+                    break 'search;
+                }
+            }
+        }
+    }
+    Ok(found)
+}
+
+#[derive(Debug, Clone)]
+pub struct IVEstimate {
+    /// Average change in the outcome per unit change in the treatment
+    pub effect: f64,
+    /// Asymptotic standard error of `effect`
+    pub std_error: f64,
+    pub n_samples: usize,
+}
+
+/// Two-stage least squares estimate of the effect of `treatment` on `outcome`.
+///
+/// Stage 1 regresses the treatment on the instruments and the conditioning set; stage 2 regresses
+/// the outcome on the fitted treatment and the conditioning set. The standard error uses the
+/// residuals of the structural equation (with the observed treatment), not the stage 2 residuals.
+pub fn two_stage_least_squares(
+    df: &DataFrame,
+    treatment: &Variable,
+    outcome: &Variable,
+    instruments: &[Variable],
+    conditioning: &[Variable],
+) -> IVEstimate {
+    assert!(!instruments.is_empty(), "At least one instrument is required");
+
+    let x = column_to_array(df, treatment).insert_axis(Axis(1));
+    let y = column_to_array(df, outcome);
+    let w = columns_to_array(df, conditioning);
+    let n = y.len();
+    // Treatment, conditioning set and intercept
+    let k = conditioning.len() + 2;
+    assert!(n > k, "Need more samples than coefficients to estimate the standard error, got {} samples for {} coefficients", n, k);
+
+    // Stage 1: treatment ~ instruments + conditioning
+    let mut stage_one = LinearRegression::new();
+    let mut first_inputs: Vec<Variable> = instruments.to_vec();
+    first_inputs.extend_from_slice(conditioning);
+    let z = columns_to_array(df, &first_inputs);
+    stage_one.fit_ndarray(z.clone(), &x.column(0).to_owned());
+    let x_hat = stage_one.predict(&z).insert_axis(Axis(1));
+
+    // Stage 2: outcome ~ fitted treatment + conditioning
+    let mut stage_two = LinearRegression::new();
+    let second_inputs = concatenate![Axis(1), x_hat, w];
+    stage_two.fit_ndarray(second_inputs.clone(), &y);
+    let effect = stage_two.weights().expect("Model was not fit")[0];
+
+    // Structural residuals use the observed treatment
+    let observed_inputs = concatenate![Axis(1), x, w];
+    let residuals: Array1<f64> = &y - &stage_two.predict(&observed_inputs);
+    let sigma2 = residuals.dot(&residuals) / (n - k) as f64;
+
+    let design = concatenate![Axis(1), Array2::<f64>::ones((n, 1)), second_inputs];
+    let xtx_inv = invert(&design.t().dot(&design)).expect("Design matrix is singular");
+    let std_error = (sigma2 * xtx_inv[[1, 1]]).sqrt();
+
+    IVEstimate { effect, std_error, n_samples: n }
+}
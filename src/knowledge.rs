@@ -0,0 +1,75 @@
+use std::collections::{HashMap, HashSet};
+use crate::dag::Variable;
+
+/// Domain knowledge for causal discovery: edges that must not or must exist, and temporal tiers.
+///
+/// A variable in a later tier can never cause one in an earlier tier. Variables without a tier
+/// are unconstrained.
+#[derive(Debug, Clone, Default)]
+pub struct BackgroundKnowledge {
+    forbidden: HashSet<(Variable, Variable)>,
+    required: HashSet<(Variable, Variable)>,
+    tiers: HashMap<Variable, usize>,
+}
+
+impl BackgroundKnowledge {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forbids the edge `from` -> `to`
+    pub fn forbid(mut self, from: &str, to: &str) -> Self {
+        self.forbidden.insert((from.to_string(), to.to_string()));
+        self
+    }
+
+    /// Requires the edge `from` -> `to`
+    pub fn require(mut self, from: &str, to: &str) -> Self {
+        self.required.insert((from.to_string(), to.to_string()));
+        self
+    }
+
+    /// Places the variables in tier `tier`; lower tiers come first in time
+    pub fn tier(mut self, tier: usize, variables: &[&str]) -> Self {
+        for v in variables {
+            self.tiers.insert(v.to_string(), tier);
+        }
+        self
+    }
+
+    /// True if `from` -> `to` is forbidden, explicitly or by the tier order
+    pub fn is_forbidden(&self, from: &str, to: &str) -> bool {
+        if self.forbidden.contains(&(from.to_string(), to.to_string())) {
+            return true;
+        }
+        match (self.tiers.get(from), self.tiers.get(to)) {
+            (Some(a), Some(b)) => a > b,
+            _ => false,
+        }
+    }
+
+    pub fn is_required(&self, from: &str, to: &str) -> bool {
+        self.required.contains(&(from.to_string(), to.to_string()))
+    }
+
+    /// Both orientations are forbidden, so A and B cannot be adjacent
+    pub fn forbids_adjacency(&self, a: &str, b: &str) -> bool {
+        self.is_forbidden(a, b) && self.is_forbidden(b, a)
+    }
+
+    /// An edge in either direction is required, so A and B must be adjacent
+    pub fn requires_adjacency(&self, a: &str, b: &str) -> bool {
+        self.is_required(a, b) || self.is_required(b, a)
+    }
+
+    /// The orientation of A - B implied by the knowledge, if any
+    pub fn orientation(&self, a: &str, b: &str) -> Option<(Variable, Variable)> {
+        if self.is_required(a, b) || (self.is_forbidden(b, a) && !self.is_forbidden(a, b)) {
+            Some((a.to_string(), b.to_string()))
+        } else if self.is_required(b, a) || (self.is_forbidden(a, b) && !self.is_forbidden(b, a)) {
+            Some((b.to_string(), a.to_string()))
+        } else {
+            None
+        }
+    }
+}
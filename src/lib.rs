@@ -1,7 +1,24 @@
 pub mod dag;
+pub mod admg;
 pub mod fcm;
 pub mod pc;
+pub mod fci;
+pub mod ges;
+pub mod bootstrap;
+pub mod lingam;
+pub mod notears;
+pub mod independence;
+pub mod pdag;
+pub mod pag;
+pub mod knowledge;
+pub mod trace;
 pub mod intervention;
 #[macro_use]
 pub mod macros;
-pub mod mechanism;
\ No newline at end of file
+pub mod mechanism;
+pub mod backdoor;
+pub mod frontdoor;
+pub mod iv;
+pub mod identification;
+mod linalg;
+pub mod stats;
\ No newline at end of file
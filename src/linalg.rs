@@ -0,0 +1,67 @@
+use ndarray::Array2;
+
+// Small dense linear algebra helpers, so we don't need a LAPACK backend
+
+/// Inverse of a square matrix by Gauss-Jordan elimination with partial pivoting.
+/// Returns `None` if the matrix is (numerically) singular.
+pub(crate) fn invert(matrix: &Array2<f64>) -> Option<Array2<f64>> {
+    let n = matrix.nrows();
+    assert_eq!(n, matrix.ncols(), "Matrix must be square");
+
+    let mut a = matrix.clone();
+    let mut inv = Array2::<f64>::eye(n);
+
+    for col in 0..n {
+        // Pick the largest pivot in this column for stability
+        let pivot = (col..n)
+            .max_by(|&i, &j| a[[i, col]].abs().total_cmp(&a[[j, col]].abs()))
+            .unwrap();
+        if a[[pivot, col]].abs() < 1e-12 { return None; }
+
+        if pivot != col {
+            for k in 0..n {
+                a.swap([pivot, k], [col, k]);
+                inv.swap([pivot, k], [col, k]);
+            }
+        }
+
+        let p = a[[col, col]];
+        a.row_mut(col).mapv_inplace(|v| v / p);
+        inv.row_mut(col).mapv_inplace(|v| v / p);
+        let (pivot_a, pivot_inv) = (a.row(col).to_owned(), inv.row(col).to_owned());
+
+        for row in 0..n {
+            if row == col { continue; }
+            let factor = a[[row, col]];
+            if factor == 0.0 { continue; }
+            a.row_mut(row).scaled_add(-factor, &pivot_a);
+            inv.row_mut(row).scaled_add(-factor, &pivot_inv);
+        }
+    }
+    Some(inv)
+}
+
+/// Matrix exponential by scaling and squaring with a truncated Taylor series.
+pub(crate) fn expm(matrix: &Array2<f64>) -> Array2<f64> {
+    let n = matrix.nrows();
+    assert_eq!(n, matrix.ncols(), "Matrix must be square");
+
+    // Scale so the infinity norm is at most 1/2, where 12 Taylor terms are accurate to f64
+    let norm = matrix.rows().into_iter()
+        .map(|row| row.iter().map(|v| v.abs()).sum::<f64>())
+        .fold(0.0, f64::max);
+    let squarings = if norm > 0.5 { (norm / 0.5).log2().ceil() as i32 } else { 0 };
+    let scaled = matrix / 2f64.powi(squarings);
+
+    let mut result = Array2::<f64>::eye(n);
+    let mut term = Array2::<f64>::eye(n);
+    for k in 1..=12 {
+        term = term.dot(&scaled) / k as f64;
+        result += &term;
+    }
+
+    for _ in 0..squarings {
+        result = result.dot(&result);
+    }
+    result
+}
@@ -21,6 +21,12 @@ pub struct LinearRegression {
     model: Option<FittedLinearRegression<f64>>,         // actual trained model
 }
 
+impl Default for LinearRegression {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl LinearRegression {
     // Constructor with predefined coefficients
     pub fn from<T: Into<f64>>(weights: Vec<f64>, bias: T, noise: T) -> Self {
@@ -50,6 +56,10 @@ impl LinearRegression {
 
     }
 
+    pub fn weights(&self) -> Option<&Array1<f64>> {
+        self.weights.as_ref()
+    }
+
     pub fn predict(&self, x: &Array2<f64>) -> Array1<f64> {
         // Case 1: use trained linfa model
         if let Some(model) = &self.model {
@@ -96,49 +106,39 @@ impl Mechanism for LinearRegression {
         self.predict(&parents_as_2d)[0]
     }
 
-    fn fit(&mut self, mut df: DataFrame, variable: Variable, fcm: &FCM) {
-        let parents = fcm.graph.get_parents(&variable);
-
-        //find out which variable it is from the dataset
-        let target_series: &Series = df.column(&variable)
-                                .expect("Error indexing variable name in provided df!")
-                                .as_series()
-                                .unwrap();
-
-        //convert target series to a 1d ndarray
-        let y: Array1<f64> = match target_series.dtype() {
-            DataType::Float64 => target_series.f64().unwrap().into_no_null_iter().collect(),
-            DataType::Float32 => target_series.f32().unwrap().into_no_null_iter().map(|v| v as f64).collect(),
-            _ => panic!("Target column must be numeric"),
-        };
-
-        df.drop_in_place(&variable).expect("Error indexing variable name to drop from provided df!");
-        //drop non-parents:
-        let df = df.select(parents).unwrap();
-        //Convert df to 2D ndarray
-        let n_rows = df.height();
-        let n_cols = df.width();
-        let mut x_vec = Vec::with_capacity(n_rows * n_cols);
-
-
-        let mut cols = df.get_columns().to_vec();
-        cols.sort_by_key(|s| s.name().to_string());
-
-        for col in cols {
-            match col.dtype() {
-                DataType::Float64 => x_vec.extend(col.f64().unwrap().into_no_null_iter()),
-                DataType::Float32 => x_vec.extend(col.f32().unwrap().into_no_null_iter().map(|v| v as f64)),
-                _ => panic!("All feature columns must be numeric"),
-            }
-        }
+    fn fit(&mut self, df: DataFrame, variable: Variable, fcm: &FCM) {
+        let mut parents = fcm.graph.get_parents(&variable);
+        //parents are sorted so the weights line up with FCM::sample
+        parents.sort();
 
-        // Create Array2 in row-major order
-        let x = Array2::from_shape_vec((n_rows, n_cols), x_vec).expect("Failed to create Array2");
+        let y = column_to_array(&df, &variable);
+        let x = columns_to_array(&df, &parents);
 
         self.fit_ndarray(x, &y);
     }
 }
 
+// Converts a numeric column of the df to a 1d ndarray. Missing values are not supported.
+pub(crate) fn column_to_array(df: &DataFrame, name: &str) -> Array1<f64> {
+    let series: Series = df.column(name)
+        .expect("Error indexing variable name in provided df!")
+        .as_materialized_series()
+        .cast(&DataType::Float64)
+        .expect("Column must be numeric");
+    assert_eq!(series.null_count(), 0,
+               "Column '{}' contains {} null values, drop or impute them first", name, series.null_count());
+    series.f64().unwrap().into_no_null_iter().collect()
+}
+
+// Converts the given numeric columns of the df to a 2d ndarray, one column per name (in order)
+pub(crate) fn columns_to_array(df: &DataFrame, names: &[Variable]) -> Array2<f64> {
+    let mut x = Array2::<f64>::zeros((df.height(), names.len()));
+    for (j, name) in names.iter().enumerate() {
+        x.column_mut(j).assign(&column_to_array(df, name));
+    }
+    x
+}
+
 pub struct EmpiricalRoot { //randomly choose a value from the history of values for that variable
     history: Vec<Value>,
 }
@@ -0,0 +1,156 @@
+use std::fmt;
+use std::ops::Deref;
+use petgraph::Direction;
+use petgraph::graph::{DiGraph, NodeIndex};
+use petgraph::visit::EdgeRef;
+use crate::dag::Variable;
+
+/// Edge mark at one end of a PAG edge
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mark {
+    /// `-`: the node is an ancestor of the other end (or of a selection variable)
+    Tail,
+    /// `>`: the node is not an ancestor of the other end
+    Arrow,
+    /// `o`: the mark is not determined
+    Circle,
+}
+
+/// Partial ancestral graph: the Markov equivalence class of MAGs, allowing hidden confounders
+/// and selection bias.
+///
+/// Each edge is stored once as (mark at source, mark at target); use [`PAG::mark`] rather than
+/// the raw petgraph edges to read the marks.
+#[derive(Clone)]
+pub struct PAG {
+    pub graph: DiGraph<Variable, (Mark, Mark)>,
+}
+
+impl Deref for PAG {
+    type Target = DiGraph<Variable, (Mark, Mark)>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.graph
+    }
+}
+
+impl Default for PAG {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PAG {
+    pub fn new() -> PAG {
+        PAG { graph: DiGraph::<Variable, (Mark, Mark)>::new() }
+    }
+
+    pub fn get_index(&self, variable: &Variable) -> Option<NodeIndex> {
+        self.graph.node_indices().find(|&node| self.graph[node].eq(variable))
+    }
+
+    pub fn node(mut self, name: &str) -> Self {
+        if self.get_index(&Variable::from(name)).is_none() {
+            self.graph.add_node(name.to_string());
+        }
+        self
+    }
+
+    pub fn variables(&self) -> Vec<Variable> {
+        self.graph.node_indices().map(|node| self.graph[node].clone()).collect()
+    }
+
+    /// Adds A `mark_a`-`mark_b` B, replacing any existing edge between the two
+    pub fn add_edge(&mut self, a: &str, b: &str, mark_a: Mark, mark_b: Mark) {
+        self.remove_edge(a, b);
+        let (a_idx, b_idx) = (self.index(a), self.index(b));
+        self.graph.add_edge(a_idx, b_idx, (mark_a, mark_b));
+    }
+
+    pub fn remove_edge(&mut self, a: &str, b: &str) {
+        let (a_idx, b_idx) = (self.index(a), self.index(b));
+        while let Some(e) = self.graph.find_edge_undirected(a_idx, b_idx) {
+            self.graph.remove_edge(e.0);
+        }
+    }
+
+    pub fn is_adjacent(&self, a: &str, b: &str) -> bool {
+        self.graph.find_edge_undirected(self.index(a), self.index(b)).is_some()
+    }
+
+    /// Every node sharing an edge with `node`
+    pub fn adjacent(&self, node: &str) -> Vec<Variable> {
+        let mut res: Vec<Variable> = self.graph.neighbors_undirected(self.index(node))
+            .map(|i| self.graph[i].clone())
+            .collect();
+        res.sort();
+        res.dedup();
+        res
+    }
+
+    /// The mark at B on the edge between A and B, if they are adjacent
+    pub fn mark(&self, a: &str, b: &str) -> Option<Mark> {
+        let (e, direction) = self.graph.find_edge_undirected(self.index(a), self.index(b))?;
+        let (at_source, at_target) = self.graph[e];
+        Some(if direction == Direction::Outgoing { at_target } else { at_source })
+    }
+
+    /// Sets the mark at B on the edge between A and B
+    pub fn set_mark(&mut self, a: &str, b: &str, mark: Mark) {
+        let (e, direction) = self.graph.find_edge_undirected(self.index(a), self.index(b))
+            .expect("Nodes are not adjacent");
+        let marks = &mut self.graph[e];
+        if direction == Direction::Outgoing { marks.1 = mark } else { marks.0 = mark }
+    }
+
+    /// True if the graph contains A -> B
+    pub fn is_directed(&self, a: &str, b: &str) -> bool {
+        self.mark(b, a) == Some(Mark::Tail) && self.mark(a, b) == Some(Mark::Arrow)
+    }
+
+    /// True if the graph contains A <-> B
+    pub fn is_bidirected(&self, a: &str, b: &str) -> bool {
+        self.mark(b, a) == Some(Mark::Arrow) && self.mark(a, b) == Some(Mark::Arrow)
+    }
+
+    /// Every edge as (A, mark at A, mark at B, B), sorted
+    pub fn edges(&self) -> Vec<(Variable, Mark, Mark, Variable)> {
+        let mut edges: Vec<(Variable, Mark, Mark, Variable)> = self.graph.edge_references()
+            .map(|e| {
+                let (a, b) = (&self.graph[e.source()], &self.graph[e.target()]);
+                let (mark_a, mark_b) = *e.weight();
+                if a <= b {
+                    (a.clone(), mark_a, mark_b, b.clone())
+                } else {
+                    (b.clone(), mark_b, mark_a, a.clone())
+                }
+            })
+            .collect();
+        edges.sort_by(|x, y| (&x.0, &x.3).cmp(&(&y.0, &y.3)));
+        edges
+    }
+
+    fn index(&self, node: &str) -> NodeIndex {
+        self.get_index(&node.to_string()).expect("Node not found")
+    }
+}
+
+fn left_symbol(mark: Mark) -> char {
+    match mark { Mark::Tail => '-', Mark::Arrow => '<', Mark::Circle => 'o' }
+}
+
+fn right_symbol(mark: Mark) -> char {
+    match mark { Mark::Tail => '-', Mark::Arrow => '>', Mark::Circle => 'o' }
+}
+
+impl fmt::Display for PAG {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "PAG {{")?;
+
+        for (a, mark_a, mark_b, b) in self.edges() {
+            writeln!(f, "  {} {}-{} {}", a, left_symbol(mark_a), right_symbol(mark_b), b)?;
+        }
+
+        writeln!(f, "}}")
+    }
+}
@@ -1,129 +1,249 @@
-use crate::dag::{Variable, DAG};
-use polars::prelude::*;
-use std::collections::{HashMap, HashSet, VecDeque};
-use std::f64::consts::SQRT_2;
-use petgraph::visit::EdgeRef;
-
-// Use your existing structs
+use crate::dag::Variable;
+use crate::independence::{CITest, CITestResult, FisherZ};
+use crate::knowledge::BackgroundKnowledge;
+use crate::pdag::PDAG;
+use crate::trace::{Observer, Trace, TraceEvent};
+pub use crate::trace::Limit;
+use log::{debug, info};
+use ndarray::Array2;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+/// How unshielded triples X - Y - Z are classified as colliders
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColliderRule {
+    /// Collider iff Y is not in the sepset found by the skeleton search
+    #[default]
+    Standard,
+    /// Re-tests X and Z given every subset of their neighbours: collider iff Y is in none of
+    /// the separating sets, non-collider iff it is in all of them, ambiguous otherwise
+    /// (Ramsey et al., 2006)
+    Conservative,
+    /// Like `Conservative`, but decided by whether Y is in less or more than half of the
+    /// separating sets; exactly half is ambiguous (Colombo & Maathuis, 2014)
+    Majority,
+}
 
 /// A "Proper" PC Algorithm implementation
 pub struct PC {
-    pub graph: DAG,
+    /// Learned CPDAG: directed edges are oriented, undirected edges are not identifiable from the data
+    pub graph: PDAG,
     pub sepsets: HashMap<(Variable, Variable), HashSet<Variable>>,
-    data: DataFrame,
-    correlation_matrix: HashMap<(Variable, Variable), f64>,
-    n_samples: usize,
+    /// The test that removed each edge, keyed like `sepsets`
+    pub sepset_tests: HashMap<(Variable, Variable), CITestResult>,
+    /// Unshielded triples (X, Y, Z), X < Z, left unoriented because the separating sets disagree
+    /// or because orienting them would have contradicted an earlier collider
+    pub ambiguous_triples: Vec<(Variable, Variable, Variable)>,
+    /// Every CI test and orientation step of the run, in order
+    pub trace: Trace,
+    /// Set if a limit stopped the search early: edges may remain that more tests would have
+    /// removed, and collider votes past the limit are ambiguous. `None` if the run completed.
+    pub truncated: Option<Limit>,
+    collider_rule: ColliderRule,
+    knowledge: BackgroundKnowledge,
+    test: Box<dyn CITest>,
+    observer: Option<Observer>,
+    trace_tests: bool,
+    max_depth: Option<usize>,
+    max_tests: Option<usize>,
+    time_budget: Option<Duration>,
+    deadline: Option<Instant>,
+    tests_run: usize,
 }
 
 impl PC {
-    pub fn new(variables: Vec<Variable>, data: DataFrame) -> Self {
-        let n_samples = data.height();
-
-        // 1. Precompute Correlation Matrix (Optimization)
-        // Calculating Pearson correlation once makes the recursive steps faster.
-        let mut corr_map = HashMap::new();
-        for i in 0..variables.len() {
-            for j in 0..variables.len() {
-                if i == j { continue; }
-                let u = &variables[i];
-                let v = &variables[j];
-
-                // Get columns from Polars
-                let s_u = data.column(u).unwrap().f64().unwrap();
-                let s_v = data.column(v).unwrap().f64().unwrap();
-
-                // Calculate Pearson Correlation
-                // Note: Polars specific API
-                let corr = pearson_corr(s_u, s_v);
-                corr_map.insert((u.clone(), v.clone()), corr);
-            }
+    /// `test` decides conditional independence, e.g. `FisherZ::new(&df)` for Gaussian data
+    pub fn new<T: CITest + 'static>(variables: Vec<Variable>, test: T) -> Self {
+        // Initialize Complete Graph (Undirected)
+        PC {
+            graph: PDAG::complete(&variables),
+            sepsets: HashMap::new(),
+            sepset_tests: HashMap::new(),
+            ambiguous_triples: Vec::new(),
+            trace: Trace::new(),
+            truncated: None,
+            collider_rule: ColliderRule::default(),
+            knowledge: BackgroundKnowledge::default(),
+            test: Box::new(test),
+            observer: None,
+            trace_tests: true,
+            max_depth: None,
+            max_tests: None,
+            time_budget: None,
+            deadline: None,
+            tests_run: 0,
         }
+    }
 
-        // 2. Initialize Complete Graph (Undirected)
-        // We represent A - B as edges A->B AND B->A
-        let mut dag = DAG::new();
-        for v in &variables { dag.add_node(v.clone()); }
+    /// Fisher-z PC from a covariance matrix of `variables` (same order) and its sample size,
+    /// for when only aggregated data is available
+    pub fn from_covariance(variables: Vec<Variable>, covariance: Array2<f64>, n_samples: usize) -> Self {
+        let test = FisherZ::from_covariance(variables.clone(), covariance, n_samples);
+        Self::new(variables, test)
+    }
 
-        for i in 0..variables.len() {
-            for j in (i + 1)..variables.len() {
-                let u = &variables[i];
-                let v = &variables[j];
-                dag = dag.edge(u, v).edge(v, u);
-            }
-        }
+    /// Fisher-z PC from a correlation matrix of `variables` (same order) and its sample size
+    pub fn from_correlation(variables: Vec<Variable>, correlation: Array2<f64>, n_samples: usize) -> Self {
+        let test = FisherZ::from_correlation(variables.clone(), correlation, n_samples);
+        Self::new(variables, test)
+    }
 
-        PC {
-            graph: dag,
-            sepsets: HashMap::new(),
-            data,
-            correlation_matrix: corr_map,
-            n_samples,
-        }
+    pub fn collider_rule(mut self, rule: ColliderRule) -> Self {
+        self.collider_rule = rule;
+        self
     }
 
-    /// Run the full pipeline
+    /// Forbidden and required edges and tiers that the search must respect
+    pub fn knowledge(mut self, knowledge: BackgroundKnowledge) -> Self {
+        self.knowledge = knowledge;
+        self
+    }
+
+    /// Called with every trace event as it happens, e.g. to stream progress elsewhere.
+    /// Events are also logged through the `log` crate: CI tests at debug level, the rest at info.
+    pub fn observer<F: Fn(&TraceEvent) + Send + Sync + 'static>(mut self, observer: F) -> Self {
+        self.observer = Some(Box::new(observer));
+        self
+    }
+
+    /// Whether `trace` keeps every CI test (default true). Large graphs can run millions of
+    /// tests; with `false` only their number is kept (`Trace::ci_test_count`), while the
+    /// observer and the log still see each one.
+    pub fn trace_tests(mut self, keep: bool) -> Self {
+        self.trace_tests = keep;
+        self
+    }
+
+    /// Largest conditioning set the skeleton search tries
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Maximum number of CI tests over the whole run
+    pub fn max_tests(mut self, tests: usize) -> Self {
+        self.max_tests = Some(tests);
+        self
+    }
+
+    /// Wall-clock budget for the whole run, measured from the start of the skeleton search
+    pub fn time_budget(mut self, budget: Duration) -> Self {
+        self.time_budget = Some(budget);
+        self
+    }
+
+    /// Run the full pipeline; `alpha` is the significance level of the independence tests
     pub fn run(&mut self, alpha: f64) {
-        println!("Phase 1: Learning Skeleton...");
+        assert!(alpha > 0.0 && alpha < 1.0, "alpha must be between 0 and 1, got {}", alpha);
+
+        info!("Phase 1: Learning Skeleton...");
         self.learn_skeleton(alpha);
 
-        println!("Phase 2: Orienting Colliders...");
-        self.orient_colliders();
+        info!("Phase 2: Orienting Colliders...");
+        self.orient_from_knowledge();
+        self.orient_colliders(alpha);
 
-        println!("Phase 3: Propagating Directions (Meek Rules)...");
+        info!("Phase 3: Propagating Directions (Meek Rules)...");
         self.orient_meek_rules();
     }
 
     // ==========================================
     // Phase 1: Skeleton Discovery
     // ==========================================
-    fn learn_skeleton(&mut self, alpha: f64) {
+    // PC-stable (Colombo & Maathuis, 2014): adjacency sets are frozen at the start of each depth
+    // and visited in sorted order, so neither the skeleton nor the sepsets depend on the order
+    // of the variables passed to `PC::new`.
+    pub(crate) fn learn_skeleton(&mut self, alpha: f64) {
+        self.deadline = self.time_budget.map(|budget| Instant::now() + budget);
+
+        // Edges ruled out by background knowledge are dropped untested and get no sepset
+        for (u, v) in self.graph.undirected_edges() {
+            if self.knowledge.forbids_adjacency(&u, &v) && !self.knowledge.requires_adjacency(&u, &v) {
+                self.remove_undirected_edge(&u, &v);
+                self.record(TraceEvent::EdgeRemoved { x: u, y: v, sepset: None });
+            }
+        }
+
         let mut depth = 0;
         loop {
             let mut edges_removed_this_round = false;
-            let nodes = self.graph.variables();
+            let mut nodes = self.graph.variables();
+            nodes.sort();
 
-            // We collect edges to remove first to avoid mutating graph while iterating
+            if self.max_depth.is_some_and(|max| depth > max) {
+                // Some edge still has enough neighbours for a conditioning set of this size
+                if nodes.iter().any(|n| self.get_degree(n) > depth) {
+                    self.truncate(Limit::Depth);
+                }
+                break;
+            }
+
+            // Removals only take effect at the next depth
+            let adjacency: HashMap<Variable, Vec<Variable>> = nodes.iter()
+                .map(|n| {
+                    let mut neighbors = self.get_neighbors(n);
+                    neighbors.sort();
+                    (n.clone(), neighbors)
+                })
+                .collect();
             let mut removals = Vec::new();
 
-            for x in &nodes {
-                let neighbors = self.get_neighbors(x);
-                for y in &neighbors {
-                    // Enforce Order to avoid duplicate checks (check A-B, skip B-A)
-                    if x >= y { continue; }
-
-                    // Potential conditioning sets are neighbors of X (excluding Y)
-                    let adj_x: Vec<Variable> = neighbors.iter()
-                        .filter(|&n| n != y)
-                        .cloned()
-                        .collect();
-
-                    if adj_x.len() < depth { continue; }
-
-                    // Check all subsets of size `depth`
-                    let combos = get_combinations(&adj_x, depth);
-                    for sepset in combos {
-                        // STATISTICAL TEST
-                        if self.is_independent(x, y, &sepset, alpha) {
+            // Enforce Order to avoid duplicate checks (check A-B, skip B-A)
+            let pairs: Vec<(Variable, Variable)> = nodes.iter()
+                .flat_map(|x| adjacency[x].iter().filter(move |y| x < *y).map(move |y| (x.clone(), y.clone())))
+                .filter(|(x, y)| !self.knowledge.requires_adjacency(x, y))
+                .collect();
+
+            // With frozen adjacencies the edges are searched independently, so the searches can
+            // run in parallel; the results are merged in the sequential order. Batches keep the
+            // limits cheap to enforce.
+            let mut truncated = false;
+            let mut start = 0;
+            while start < pairs.len() {
+                let tests_left = self.max_tests.map(|max| max - self.tests_run);
+                let end = batch_end(&pairs, start, &adjacency, depth, tests_left);
+                let batch = &pairs[start..end];
+                start = end;
+                let budget = Budget { tests: tests_left, deadline: self.deadline };
+                let test = &*self.test;
+                let search = |(x, y): &(Variable, Variable)| search_sepset(test, &adjacency, x, y, depth, alpha, budget);
+                #[cfg(feature = "parallel")]
+                let searches: Vec<SepsetSearch> = batch.par_iter().map(search).collect();
+                #[cfg(not(feature = "parallel"))]
+                let searches: Vec<SepsetSearch> = batch.iter().map(search).collect();
+
+                for ((x, y), (tests, stopped)) in batch.iter().zip(searches) {
+                    for (sepset, result) in tests {
+                        if self.record_test(x, y, &sepset, result, alpha) {
                             // Found separation!
                             removals.push((x.clone(), y.clone()));
+                            self.record(TraceEvent::EdgeRemoved { x: x.clone(), y: y.clone(), sepset: Some(sepset.clone()) });
 
-                            // Record SepSet
-                            let mut key = vec![x.clone(), y.clone()];
-                            key.sort();
+                            // Record SepSet (x < y already)
                             let sep_set_data: HashSet<Variable> = sepset.into_iter().collect();
-                            self.sepsets.insert((key[0].clone(), key[1].clone()), sep_set_data);
+                            self.sepsets.insert((x.clone(), y.clone()), sep_set_data);
+                            self.sepset_tests.insert((x.clone(), y.clone()), result);
 
                             edges_removed_this_round = true;
-                            break; // Stop looking for other sepsets for this edge
                         }
                     }
+                    // The search had a test left to run but no budget for it
+                    if let Some(limit) = stopped {
+                        self.truncate(limit);
+                        truncated = true;
+                        break;
+                    }
                 }
+                if truncated { break; }
             }
 
             // Apply removals
             for (u, v) in removals {
                 self.remove_undirected_edge(&u, &v);
             }
+            if truncated { break; }
 
             if !edges_removed_this_round {
                 // If we went through a whole depth and removed nothing, or
@@ -139,24 +259,111 @@ impl PC {
     // ==========================================
     // Phase 2: Unshielded Colliders
     // ==========================================
-    fn orient_colliders(&mut self) {
+    fn orient_from_knowledge(&mut self) {
+        for (u, v) in self.graph.undirected_edges() {
+            if let Some((from, to)) = self.knowledge.orientation(&u, &v) {
+                self.orient_directed(&from, &to);
+                self.record(TraceEvent::Knowledge { from, to });
+            }
+        }
+    }
+
+    fn orient_colliders(&mut self, alpha: f64) {
         let triples = self.find_unshielded_triples();
         for (x, y, z) in triples {
-            let mut key = vec![x.clone(), z.clone()];
+            let mut key = [x.clone(), z.clone()];
             key.sort();
             let k_tuple = (key[0].clone(), key[1].clone());
 
-            // Check if Y is in the Separation Set of (X, Z)
-            let default_set = HashSet::new();
-            let sepset = self.sepsets.get(&k_tuple).unwrap_or(&default_set);
+            // X and Z were separated by background knowledge rather than a test
+            let Some(sepset) = self.sepsets.get(&k_tuple) else { continue; };
+
+            let is_collider = match self.collider_rule {
+                // Check if Y is in the Separation Set of (X, Z)
+                ColliderRule::Standard => Some(!sepset.contains(&y)),
+                rule => self.vote_on_triple(&x, &y, &z, alpha, rule),
+            };
+
+            if is_collider == Some(true)
+                && (self.knowledge.is_forbidden(&x, &y) || self.knowledge.is_forbidden(&z, &y)) {
+                self.record(TraceEvent::ForbiddenCollider { x, y, z });
+                continue;
+            }
+
+            match is_collider {
+                Some(true) => {
+                    // Orient X -> Y <- Z, unless an earlier collider already points out of Y
+                    if self.graph.is_directed(&y, &x) || self.graph.is_directed(&y, &z) {
+                        self.mark_ambiguous(&x, &y, &z);
+                        if !self.graph.is_directed(&y, &x) { self.orient_directed(&x, &y); }
+                        if !self.graph.is_directed(&y, &z) { self.orient_directed(&z, &y); }
+                        self.record(TraceEvent::ConflictingCollider { x, y, z });
+                    } else {
+                        self.orient_directed(&x, &y);
+                        self.orient_directed(&z, &y);
+                        self.record(TraceEvent::Collider { x, y, z });
+                    }
+                }
+                Some(false) => {}
+                None => {
+                    self.mark_ambiguous(&x, &y, &z);
+                    self.record(TraceEvent::AmbiguousTriple { x, y, z });
+                }
+            }
+        }
+    }
+
+    // Conservative / majority decision on X - Y - Z: collider (true), non-collider (false) or
+    // ambiguous (None), from every subset of adj(X) \ Z and adj(Z) \ X that separates X and Z
+    fn vote_on_triple(&mut self, x: &str, y: &str, z: &str, alpha: f64, rule: ColliderRule) -> Option<bool> {
+        let mut pools = Vec::new();
+        for (a, b) in [(x, z), (z, x)] {
+            let mut pool: Vec<Variable> = self.get_neighbors(a).into_iter().filter(|n| n != b).collect();
+            pool.sort();
+            pools.push(pool);
+        }
+
+        let mut candidates: Vec<Vec<Variable>> = Vec::new();
+        for pool in &pools {
+            for size in 0..=pool.len() {
+                for subset in get_combinations(pool, size) {
+                    if !candidates.contains(&subset) { candidates.push(subset); }
+                }
+            }
+        }
 
-            if !sepset.contains(&y) {
-                // Orient X -> Y <- Z
-                self.orient_directed(&x, &y);
-                self.orient_directed(&z, &y);
-                println!("Oriented Collider: {} -> {} <- {}", x, y, z);
+        let (mut separating, mut containing_y) = (0, 0);
+        for subset in candidates {
+            // Out of budget: the triple stays ambiguous
+            if self.limit_reached() { return None; }
+            if self.is_independent(x, z, &subset, alpha).0 {
+                separating += 1;
+                if subset.iter().any(|v| v == y) { containing_y += 1; }
             }
         }
+
+        // No separating set any more: nothing to go on
+        if separating == 0 { return None; }
+        match rule {
+            ColliderRule::Conservative if containing_y == 0 => Some(true),
+            ColliderRule::Conservative if containing_y == separating => Some(false),
+            ColliderRule::Majority if 2 * containing_y < separating => Some(true),
+            ColliderRule::Majority if 2 * containing_y > separating => Some(false),
+            _ => None,
+        }
+    }
+
+    fn mark_ambiguous(&mut self, x: &str, y: &str, z: &str) {
+        let (a, c) = if x < z { (x, z) } else { (z, x) };
+        let triple = (a.to_string(), y.to_string(), c.to_string());
+        if !self.ambiguous_triples.contains(&triple) {
+            self.ambiguous_triples.push(triple);
+        }
+    }
+
+    fn is_ambiguous(&self, x: &str, y: &str, z: &str) -> bool {
+        let (a, c) = if x < z { (x, z) } else { (z, x) };
+        self.ambiguous_triples.iter().any(|(p, q, r)| p == a && q == y && r == c)
     }
 
     // ==========================================
@@ -166,107 +373,124 @@ impl PC {
         let mut change = true;
         while change {
             change = false;
-            let nodes = self.graph.variables();
-
-            // Rule 1: X -> Y - Z  =>  X -> Y -> Z
-            // (If X->Y and Y-Z (undirected), and X not connected to Z)
-            for y in &nodes {
-                let y_parents = self.graph.get_parents(y); // Directed incoming
-                let y_neighbors = self.get_undirected_neighbors(y); // Undirected connected
-
-                for x in &y_parents {
-                    for z in &y_neighbors {
-                        if !self.are_adjacent(x, z) {
-                            // Apply Rule 1
-                            self.orient_directed(y, z);
-                            change = true;
-                            println!("Meek Rule 1: {} -> {} -> {}", x, y, z);
-                        }
+
+            for (u, v) in self.graph.undirected_edges() {
+                for (a, b) in [(&u, &v), (&v, &u)] {
+                    // An earlier orientation in this pass may already have handled the edge
+                    if !self.graph.is_undirected(a, b) { continue; }
+                    if self.knowledge.is_forbidden(a, b) { continue; }
+
+                    if let Some(rule) = self.meek_rule_for(a, b) {
+                        self.orient_directed(a, b);
+                        change = true;
+                        self.record(TraceEvent::MeekRule { rule, from: a.clone(), to: b.clone() });
                     }
                 }
             }
+        }
+    }
+
+    // Which Meek rule (if any) forces the undirected edge A - B to become A -> B
+    fn meek_rule_for(&self, a: &str, b: &str) -> Option<u8> {
+        // Ambiguous triples are never treated as known non-colliders
 
-            // (Rule 2 and 3 can be added here for completeness, but Rule 1 is the workhorse)
+        // Rule 1: C -> A - B, C and B not adjacent
+        if self.graph.get_parents(a).iter().any(|c| !self.are_adjacent(c, b) && !self.is_ambiguous(c, a, b)) {
+            return Some(1);
         }
+
+        // Rule 2: A -> C -> B (otherwise B -> A would close a cycle)
+        if self.graph.get_children(a).iter().any(|c| self.graph.is_directed(c, b)) {
+            return Some(2);
+        }
+
+        // Rule 3: A - C -> B and A - D -> B, C and D not adjacent
+        let undirected_a: Vec<Variable> = self.graph.undirected_neighbors(a)
+            .into_iter()
+            .filter(|c| c != b)
+            .collect();
+        let into_b: Vec<&Variable> = undirected_a.iter()
+            .filter(|c| self.graph.is_directed(c, b))
+            .collect();
+        for (i, c) in into_b.iter().enumerate() {
+            if into_b[i + 1..].iter().any(|d| !self.are_adjacent(c, d) && !self.is_ambiguous(c, a, d)) {
+                return Some(3);
+            }
+        }
+
+        // Rule 4: A - C -> D -> B, C and B not adjacent, A adjacent to D
+        for c in undirected_a.iter().filter(|c| !self.are_adjacent(c, b) && !self.is_ambiguous(c, a, b)) {
+            let chain = self.graph.get_children(c).into_iter()
+                .any(|d| self.graph.is_directed(&d, b) && self.are_adjacent(a, &d));
+            if chain {
+                return Some(4);
+            }
+        }
+
+        None
     }
 
     // ==========================================
-    // Statistical Math (Fisher Z-Test)
+    // Statistical Test
     // ==========================================
-    fn is_independent(&self, x: &str, y: &str, z: &[Variable], alpha: f64) -> bool {
-        // 1. Calculate Partial Correlation
-        let r = self.partial_correlation(x, y, z);
-
-        // 2. Fisher Z-Transform
-        if r.abs() >= 1.0 { return false; } // Correlation of 1 means dependent
-        let z_stat = 0.5 * ((1.0 + r) / (1.0 - r)).ln();
-
-        // 3. Z-Test Statistic
-        // Degrees of freedom = N - |Z| - 3
-        let n = self.n_samples as f64;
-        let k = z.len() as f64;
-        let scale = (n - k - 3.0).sqrt();
-        let statistic = (scale * z_stat).abs();
-
-        // 4. Critical Value (Standard Normal)
-        // Approx for alpha=0.05 is 1.96.
-        // We can use a simple inverse error function approximation or hardcode thresholds.
-        let critical_val = match alpha {
-            0.01 => 2.576,
-            0.05 => 1.960,
-            0.10 => 1.645,
-            _ => 1.960 // Default
-        };
-
-        // If statistic < critical, we cannot reject Null Hypothesis (Independence)
-        // Therefore: They ARE Independent.
-        statistic < critical_val
-    }
-
-    /// Recursive Partial Correlation: rho_xy|z
-    fn partial_correlation(&self, x: &str, y: &str, z: &[Variable]) -> f64 {
-        if z.is_empty() {
-            return *self.correlation_matrix.get(&(x.to_string(), y.to_string())).unwrap_or(&0.0);
-        }
+    pub(crate) fn is_independent(&mut self, x: &str, y: &str, z: &[Variable], alpha: f64) -> (bool, CITestResult) {
+        let result = self.test.test(x, y, z);
+        (self.record_test(x, y, z, result, alpha), result)
+    }
 
-        // Pop last element of Z
-        let k = &z[0];
-        let z_rest = &z[1..];
+    // Traces a test that was already run and returns whether X and Y are independent
+    fn record_test(&mut self, x: &str, y: &str, z: &[Variable], result: CITestResult, alpha: f64) -> bool {
+        // If we cannot reject the Null Hypothesis (Independence) at level alpha,
+        // then they ARE Independent.
+        let independent = result.p_value > alpha;
+        self.tests_run += 1;
+        self.record(TraceEvent::CITest {
+            x: x.to_string(),
+            y: y.to_string(),
+            conditioning_set: z.to_vec(),
+            statistic: result.statistic,
+            p_value: result.p_value,
+            independent,
+        });
+        independent
+    }
 
-        let r_xy = self.partial_correlation(x, y, z_rest);
-        let r_xk = self.partial_correlation(x, k, z_rest);
-        let r_yk = self.partial_correlation(y, k, z_rest);
+    // True once the test or time budget is used up; call it only right before a test, since it
+    // marks the run as truncated
+    fn limit_reached(&mut self) -> bool {
+        let budget = Budget { tests: self.max_tests, deadline: self.deadline };
+        let Some(limit) = budget.exceeded(self.tests_run) else { return false; };
+        self.truncate(limit);
+        true
+    }
 
-        let num = r_xy - (r_xk * r_yk);
-        let den = ((1.0 - r_xk.powi(2)) * (1.0 - r_yk.powi(2))).sqrt();
+    fn truncate(&mut self, limit: Limit) {
+        if self.truncated.is_none() {
+            self.truncated = Some(limit);
+            self.record(TraceEvent::Truncated { limit });
+        }
+    }
 
-        if den == 0.0 { 0.0 } else { num / den }
+    fn record(&mut self, event: TraceEvent) {
+        match event {
+            TraceEvent::CITest { .. } => debug!("{}", event),
+            _ => info!("{}", event),
+        }
+        if let Some(observer) = &self.observer {
+            observer(&event);
+        }
+        if !self.trace_tests && matches!(event, TraceEvent::CITest { .. }) {
+            self.trace.ci_test_count += 1;
+        } else {
+            self.trace.push(event);
+        }
     }
 
     // ==========================================
     // Graph Helpers
     // ==========================================
     fn get_neighbors(&self, node: &str) -> Vec<Variable> {
-        let idx = self.graph.get_index(&node.to_string()).unwrap();
-        // Standard Neighbors (Incoming + Outgoing)
-        self.graph.graph.neighbors_undirected(idx)
-            .map(|i| self.graph.graph[i].clone())
-            .collect()
-    }
-
-    // Specifically finds 'Y' where X - Y exists (bidirectional)
-    fn get_undirected_neighbors(&self, node: &str) -> Vec<Variable> {
-        let idx = self.graph.get_index(&node.to_string()).unwrap();
-        let mut res = Vec::new();
-        for neighbor_idx in self.graph.graph.neighbors_undirected(idx) {
-            let neighbor_name = self.graph.graph[neighbor_idx].clone();
-            // Check for edge back
-            if self.graph.graph.contains_edge(idx, neighbor_idx) &&
-                self.graph.graph.contains_edge(neighbor_idx, idx) {
-                res.push(neighbor_name);
-            }
-        }
-        res
+        self.graph.adjacent(node)
     }
 
     fn get_degree(&self, node: &str) -> usize {
@@ -274,38 +498,25 @@ impl PC {
     }
 
     fn remove_undirected_edge(&mut self, u: &str, v: &str) {
-        let u_idx = self.graph.get_index(&u.to_string()).unwrap();
-        let v_idx = self.graph.get_index(&v.to_string()).unwrap();
-        if let Some(e) = self.graph.graph.find_edge(u_idx, v_idx) { self.graph.graph.remove_edge(e); }
-        if let Some(e) = self.graph.graph.find_edge(v_idx, u_idx) { self.graph.graph.remove_edge(e); }
+        self.graph.remove_edge(u, v);
     }
 
-    // Sets X -> Y (removes Y -> X)
+    // Sets X -> Y (replaces X - Y or Y -> X)
     fn orient_directed(&mut self, x: &str, y: &str) {
-        let u_idx = self.graph.get_index(&x.to_string()).unwrap();
-        let v_idx = self.graph.get_index(&y.to_string()).unwrap();
-
-        // Remove Y -> X
-        if let Some(e) = self.graph.graph.find_edge(v_idx, u_idx) {
-            self.graph.graph.remove_edge(e);
-        }
-        // Ensure X -> Y
-        if self.graph.graph.find_edge(u_idx, v_idx).is_none() {
-            self.graph.graph.add_edge(u_idx, v_idx, ());
-        }
+        self.graph.orient(x, y);
     }
 
     fn are_adjacent(&self, x: &str, y: &str) -> bool {
-        let u_idx = self.graph.get_index(&x.to_string()).expect("Node missing");
-        let v_idx = self.graph.get_index(&y.to_string()).expect("Node missing");
-        self.graph.graph.contains_edge(u_idx, v_idx) || self.graph.graph.contains_edge(v_idx, u_idx)
+        self.graph.is_adjacent(x, y)
     }
 
     fn find_unshielded_triples(&self) -> Vec<(Variable, Variable, Variable)> {
         let mut triples = Vec::new();
-        let nodes = self.graph.variables();
+        let mut nodes = self.graph.variables();
+        nodes.sort();
         for y in &nodes {
-            let neighbors = self.get_neighbors(y);
+            let mut neighbors = self.get_neighbors(y);
+            neighbors.sort();
             if neighbors.len() < 2 { continue; }
 
             for i in 0..neighbors.len() {
@@ -324,28 +535,117 @@ impl PC {
 
 // --- Utilities ---
 
-fn pearson_corr(a: &ChunkedArray<Float64Type>, b: &ChunkedArray<Float64Type>) -> f64 {
-    let mean_a = a.mean().unwrap_or(0.0);
-    let mean_b = b.mean().unwrap_or(0.0);
-
-    let mut num = 0.0;
-    let mut den_a = 0.0;
-    let mut den_b = 0.0;
-
-    for (val_a, val_b) in a.into_iter().zip(b.into_iter()) {
-        if let (Some(va), Some(vb)) = (val_a, val_b) {
-            let da = va - mean_a;
-            let db = vb - mean_b;
-            num += da * db;
-            den_a += da * da;
-            den_b += db * db;
+// Edges searched per batch of the skeleton phase
+#[cfg(feature = "parallel")]
+fn batch_size() -> usize {
+    4 * rayon::current_num_threads()
+}
+
+#[cfg(not(feature = "parallel"))]
+fn batch_size() -> usize {
+    1
+}
+
+// End of the batch of pairs starting at `start`. With a test limit, a batch only takes the
+// pairs whose searches can all run to completion within the tests left, so parallel searches
+// never overspend it; a first pair that does not fit is searched alone and stops at the limit.
+fn batch_end(
+    pairs: &[(Variable, Variable)],
+    start: usize,
+    adjacency: &HashMap<Variable, Vec<Variable>>,
+    depth: usize,
+    tests_left: Option<usize>,
+) -> usize {
+    let max_end = (start + batch_size()).min(pairs.len());
+    let Some(mut left) = tests_left else { return max_end; };
+
+    let mut end = start;
+    while end < max_end {
+        // Most tests the search can run: every subset from each side
+        let (x, y) = &pairs[end];
+        let most = binomial(adjacency[x].len() - 1, depth).saturating_add(binomial(adjacency[y].len() - 1, depth));
+        if most > left { break; }
+        left -= most;
+        end += 1;
+    }
+    end.max(start + 1)
+}
+
+fn binomial(n: usize, k: usize) -> usize {
+    if k > n { return 0; }
+    (0..k.min(n - k))
+        .try_fold(1usize, |acc, i| acc.checked_mul(n - i).map(|v| v / (i + 1)))
+        .unwrap_or(usize::MAX)
+}
+
+// What one sepset search may still spend
+#[derive(Clone, Copy)]
+struct Budget {
+    tests: Option<usize>,
+    deadline: Option<Instant>,
+}
+
+impl Budget {
+    // The limit that rules out another test after `tests_run`, if any
+    fn exceeded(&self, tests_run: usize) -> Option<Limit> {
+        if self.tests.is_some_and(|max| tests_run >= max) {
+            Some(Limit::Tests)
+        } else if self.deadline.is_some_and(|d| Instant::now() >= d) {
+            Some(Limit::Time)
+        } else {
+            None
         }
     }
+}
+
+// Every test a sepset search ran, in order, and the limit that stopped it before a test it
+// still had to run
+type SepsetSearch = (Vec<(Vec<Variable>, CITestResult)>, Option<Limit>);
+
+// Tests X and Y given the size-`depth` subsets of their frozen neighbourhoods, stopping at the
+// first separating set (the last test) or when the budget runs out.
+fn search_sepset(
+    test: &dyn CITest,
+    adjacency: &HashMap<Variable, Vec<Variable>>,
+    x: &str,
+    y: &str,
+    depth: usize,
+    alpha: f64,
+    budget: Budget,
+) -> SepsetSearch {
+    // Potential conditioning sets are neighbors of X (excluding Y),
+    // then neighbors of Y (excluding X). Each pair is only visited once
+    // (X < Y), so a separating set that lies among the neighbors of Y
+    // alone would otherwise never be tried.
+    let adj_x: Vec<Variable> = adjacency[x].iter()
+        .filter(|&n| n != y)
+        .cloned()
+        .collect();
+    let adj_y: Vec<Variable> = adjacency[y].iter()
+        .filter(|&n| n != x)
+        .cloned()
+        .collect();
+
+    // Check all subsets of size `depth`
+    let mut combos = get_combinations(&adj_x, depth);
+    for subset in get_combinations(&adj_y, depth) {
+        if !combos.contains(&subset) { combos.push(subset); }
+    }
 
-    if den_a == 0.0 || den_b == 0.0 { 0.0 } else { num / (den_a.sqrt() * den_b.sqrt()) }
+    let mut tests = Vec::new();
+    for sepset in combos {
+        if let Some(limit) = budget.exceeded(tests.len()) { return (tests, Some(limit)); }
+        // STATISTICAL TEST
+        let result = test.test(x, y, &sepset);
+        tests.push((sepset, result));
+        if result.p_value > alpha {
+            break; // Stop looking for other sepsets for this edge
+        }
+    }
+    (tests, None)
 }
 
-fn get_combinations(pool: &[Variable], k: usize) -> Vec<Vec<Variable>> {
+pub(crate) fn get_combinations(pool: &[Variable], k: usize) -> Vec<Vec<Variable>> {
     if k == 0 { return vec![vec![]]; }
     if pool.is_empty() { return vec![]; }
 
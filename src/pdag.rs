@@ -0,0 +1,366 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::ops::Deref;
+use petgraph::Direction;
+use petgraph::algo::is_cyclic_directed;
+use petgraph::graph::{DiGraph, NodeIndex};
+use petgraph::visit::EdgeRef;
+use crate::dag::{Variable, DAG};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    /// A -> B
+    Directed,
+    /// A - B: the orientation is not determined
+    Undirected,
+}
+
+/// Partially directed acyclic graph, e.g. a CPDAG representing a Markov equivalence class.
+///
+/// Undirected edges are stored once, in the direction they were added; use the query
+/// methods rather than the raw petgraph edges to inspect the structure.
+#[derive(Clone)]
+pub struct PDAG {
+    pub graph: DiGraph<Variable, Edge>,
+}
+
+impl Deref for PDAG {
+    type Target = DiGraph<Variable, Edge>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.graph
+    }
+}
+
+impl Default for PDAG {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PDAG {
+    pub fn new() -> PDAG {
+        PDAG { graph: DiGraph::<Variable, Edge>::new() }
+    }
+
+    /// Complete undirected graph over the variables
+    pub fn complete(variables: &[Variable]) -> PDAG {
+        let mut pdag = PDAG::new();
+        for v in variables { pdag.add_node(v.clone()); }
+        for i in 0..variables.len() {
+            for j in (i + 1)..variables.len() {
+                pdag.add_undirected(&variables[i], &variables[j]);
+            }
+        }
+        pdag
+    }
+
+    /// The CPDAG (essential graph) of the DAG's Markov equivalence class: compelled edges stay
+    /// directed and reversible edges become undirected (Chickering, 1995).
+    pub fn from_dag(dag: &DAG) -> PDAG {
+        let order = dag.sort();
+        let rank: HashMap<&Variable, usize> = order.iter().enumerate().map(|(i, v)| (v, i)).collect();
+        let parents: HashMap<&Variable, HashSet<Variable>> = order.iter()
+            .map(|v| (v, dag.get_parents(v).into_iter().collect()))
+            .collect();
+
+        // Order edges: by head ascending, then by tail descending (in topological order)
+        let mut edges: Vec<(Variable, Variable)> = Vec::new();
+        for y in &order {
+            let mut tails: Vec<&Variable> = parents[y].iter().collect();
+            tails.sort_by_key(|x| std::cmp::Reverse(rank[x]));
+            edges.extend(tails.into_iter().map(|x| (x.clone(), y.clone())));
+        }
+
+        // Label edges compelled (true) or reversible (false)
+        let mut label: HashMap<(Variable, Variable), bool> = HashMap::new();
+        for (x, y) in &edges {
+            if label.contains_key(&(x.clone(), y.clone())) { continue; }
+
+            let mut done = false;
+            for w in &parents[x] {
+                if label.get(&(w.clone(), x.clone())) != Some(&true) { continue; }
+                if !parents[y].contains(w) {
+                    for z in &parents[y] {
+                        label.insert((z.clone(), y.clone()), true);
+                    }
+                    done = true;
+                    break;
+                }
+                label.insert((w.clone(), y.clone()), true);
+            }
+            if done { continue; }
+
+            let compelled = parents[y].iter().any(|z| z != x && !parents[x].contains(z));
+            for z in &parents[y] {
+                label.entry((z.clone(), y.clone())).or_insert(compelled);
+            }
+        }
+
+        let mut pdag = PDAG::new();
+        for v in dag.variables() { pdag.add_node(v); }
+        for (x, y) in &edges {
+            if label[&(x.clone(), y.clone())] {
+                pdag.add_directed(x, y);
+            } else {
+                pdag.add_undirected(x, y);
+            }
+        }
+        pdag
+    }
+
+    pub fn get_index(&self, variable: &Variable) -> Option<NodeIndex> {
+        self.graph.node_indices().find(|&node| self.graph[node].eq(variable))
+    }
+
+    pub fn node(mut self, name: &str) -> Self {
+        if self.get_index(&Variable::from(name)).is_none() {
+            self.graph.add_node(name.to_string());
+        }
+        self
+    }
+
+    pub fn add_node<S: Into<String>>(&mut self, name: S) {
+        self.graph.add_node(name.into());
+    }
+
+    pub fn variables(&self) -> Vec<Variable> {
+        self.graph.node_indices().map(|node| self.graph[node].clone()).collect()
+    }
+
+    /// Adds A -> B, replacing any existing edge between the two
+    pub fn add_directed(&mut self, a: &str, b: &str) {
+        self.remove_edge(a, b);
+        let (a_idx, b_idx) = (self.index(a), self.index(b));
+        self.graph.add_edge(a_idx, b_idx, Edge::Directed);
+    }
+
+    /// Adds A - B, replacing any existing edge between the two
+    pub fn add_undirected(&mut self, a: &str, b: &str) {
+        self.remove_edge(a, b);
+        let (a_idx, b_idx) = (self.index(a), self.index(b));
+        self.graph.add_edge(a_idx, b_idx, Edge::Undirected);
+    }
+
+    /// Removes whatever edge joins A and B
+    pub fn remove_edge(&mut self, a: &str, b: &str) {
+        let (a_idx, b_idx) = (self.index(a), self.index(b));
+        while let Some(e) = self.graph.find_edge_undirected(a_idx, b_idx) {
+            self.graph.remove_edge(e.0);
+        }
+    }
+
+    /// Orients the edge between A and B as A -> B
+    pub fn orient(&mut self, a: &str, b: &str) {
+        self.add_directed(a, b);
+    }
+
+    pub fn is_adjacent(&self, a: &str, b: &str) -> bool {
+        self.graph.find_edge_undirected(self.index(a), self.index(b)).is_some()
+    }
+
+    /// True if the graph contains A -> B
+    pub fn is_directed(&self, a: &str, b: &str) -> bool {
+        self.graph.find_edge(self.index(a), self.index(b))
+            .is_some_and(|e| self.graph[e] == Edge::Directed)
+    }
+
+    /// True if the graph contains A - B
+    pub fn is_undirected(&self, a: &str, b: &str) -> bool {
+        self.graph.find_edge_undirected(self.index(a), self.index(b))
+            .is_some_and(|(e, _)| self.graph[e] == Edge::Undirected)
+    }
+
+    /// Every node sharing an edge (of any kind) with `node`
+    pub fn adjacent(&self, node: &str) -> Vec<Variable> {
+        let idx = self.index(node);
+        let mut res: Vec<Variable> = self.graph.neighbors_undirected(idx)
+            .map(|i| self.graph[i].clone())
+            .collect();
+        res.dedup();
+        res
+    }
+
+    /// Parents through directed edges
+    pub fn get_parents(&self, node: &str) -> Vec<Variable> {
+        let idx = self.index(node);
+        self.graph.edges_directed(idx, Direction::Incoming)
+            .filter(|e| *e.weight() == Edge::Directed)
+            .map(|e| self.graph[e.source()].clone())
+            .collect()
+    }
+
+    pub fn get_children(&self, node: &str) -> Vec<Variable> {
+        let idx = self.index(node);
+        self.graph.edges_directed(idx, Direction::Outgoing)
+            .filter(|e| *e.weight() == Edge::Directed)
+            .map(|e| self.graph[e.target()].clone())
+            .collect()
+    }
+
+    /// Nodes joined to `node` by an undirected edge
+    pub fn undirected_neighbors(&self, node: &str) -> Vec<Variable> {
+        let idx = self.index(node);
+        self.graph.edges_directed(idx, Direction::Outgoing)
+            .filter(|e| *e.weight() == Edge::Undirected)
+            .map(|e| self.graph[e.target()].clone())
+            .chain(self.graph.edges_directed(idx, Direction::Incoming)
+                .filter(|e| *e.weight() == Edge::Undirected)
+                .map(|e| self.graph[e.source()].clone()))
+            .collect()
+    }
+
+    pub fn directed_edges(&self) -> Vec<(Variable, Variable)> {
+        self.edges_of(Edge::Directed)
+    }
+
+    pub fn undirected_edges(&self) -> Vec<(Variable, Variable)> {
+        self.edges_of(Edge::Undirected)
+    }
+
+    /// Unshielded colliders A -> B <- C (A and C not adjacent), with A < C
+    pub fn unshielded_colliders(&self) -> Vec<(Variable, Variable, Variable)> {
+        let mut colliders = Vec::new();
+        for b in self.variables() {
+            let parents = self.get_parents(&b);
+            for a in &parents {
+                for c in &parents {
+                    if a < c && !self.is_adjacent(a, c) {
+                        colliders.push((a.clone(), b.clone(), c.clone()));
+                    }
+                }
+            }
+        }
+        colliders.sort();
+        colliders
+    }
+
+    /// The DAG, if every edge is directed
+    pub fn to_dag(&self) -> Option<DAG> {
+        if !self.undirected_edges().is_empty() {
+            return None;
+        }
+        let dag = DAG { graph: self.graph.map(|_, v| v.clone(), |_, _| ()) };
+        if is_cyclic_directed(&dag.graph) { None } else { Some(dag) }
+    }
+
+    /// A DAG with the same skeleton and unshielded colliders that keeps every directed edge,
+    /// if one exists (Dor & Tarsi, 1992)
+    pub fn consistent_extension(&self) -> Option<DAG> {
+        let mut remaining = self.clone();
+        let mut dag = DAG { graph: self.graph.map(|_, v| v.clone(), |_, _| ()) };
+        dag.graph.clear_edges();
+        for (a, b) in self.directed_edges() {
+            dag = dag.edge(&a, &b);
+        }
+
+        let mut nodes = self.variables();
+        nodes.sort();
+        while !nodes.is_empty() {
+            // A sink whose undirected neighbours are adjacent to all of its other neighbours
+            let pos = nodes.iter().position(|x| {
+                remaining.get_children(x).is_empty()
+                    && remaining.undirected_neighbors(x).iter().all(|y| {
+                        remaining.adjacent(x).iter().all(|z| z == y || remaining.is_adjacent(y, z))
+                    })
+            })?;
+            let x = nodes.remove(pos);
+
+            for y in remaining.undirected_neighbors(&x) {
+                dag = dag.edge(&y, &x);
+            }
+            for y in remaining.adjacent(&x) {
+                remaining.remove_edge(&x, &y);
+            }
+        }
+        Some(dag)
+    }
+
+    /// Every DAG obtained by orienting the undirected edges without creating a cycle or a new
+    /// unshielded collider. For a CPDAG these are exactly the members of its equivalence class.
+    ///
+    /// The enumeration is exhaustive and grows exponentially with the number of undirected edges.
+    pub fn all_dags(&self) -> Vec<DAG> {
+        let undirected = self.undirected_edges();
+        let colliders = self.unshielded_colliders();
+        let mut found = Vec::new();
+        let mut current = self.clone();
+        self.extend_orientations(&mut current, &undirected, &colliders, &mut found);
+        found
+    }
+
+    fn extend_orientations(
+        &self,
+        current: &mut PDAG,
+        remaining: &[(Variable, Variable)],
+        colliders: &[(Variable, Variable, Variable)],
+        found: &mut Vec<DAG>,
+    ) {
+        let Some(((a, b), rest)) = remaining.split_first() else {
+            if current.unshielded_colliders() == colliders
+                && let Some(dag) = current.to_dag() {
+                found.push(dag);
+            }
+            return;
+        };
+
+        for (from, to) in [(a, b), (b, a)] {
+            current.orient(from, to);
+            if !current.creates_new_collider(to, colliders) && !current.has_directed_cycle() {
+                self.extend_orientations(current, rest, colliders, found);
+            }
+        }
+        current.add_undirected(a, b);
+    }
+
+    // Does `head` have two non-adjacent parents that don't form one of the allowed colliders?
+    fn creates_new_collider(&self, head: &str, colliders: &[(Variable, Variable, Variable)]) -> bool {
+        let parents = self.get_parents(head);
+        for a in &parents {
+            for c in &parents {
+                if a < c && !self.is_adjacent(a, c)
+                    && !colliders.contains(&(a.clone(), head.to_string(), c.clone())) {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    fn has_directed_cycle(&self) -> bool {
+        let directed = self.graph.filter_map(
+            |_, v| Some(v.clone()),
+            |_, e| if *e == Edge::Directed { Some(()) } else { None },
+        );
+        is_cyclic_directed(&directed)
+    }
+
+    fn edges_of(&self, kind: Edge) -> Vec<(Variable, Variable)> {
+        self.graph.edge_references()
+            .filter(|e| *e.weight() == kind)
+            .map(|e| (self.graph[e.source()].clone(), self.graph[e.target()].clone()))
+            .collect()
+    }
+
+    fn index(&self, node: &str) -> NodeIndex {
+        self.get_index(&node.to_string()).expect("Node not found")
+    }
+}
+
+impl fmt::Display for PDAG {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "PDAG {{")?;
+
+        for node_idx in self.graph.node_indices() {
+            let name = &self.graph[node_idx];
+            writeln!(f, "  {} -> {:?}", name, self.get_children(name))?;
+
+            let undirected = self.undirected_neighbors(name);
+            if !undirected.is_empty() {
+                writeln!(f, "  {} -- {:?}", name, undirected)?;
+            }
+        }
+
+        writeln!(f, "}}")
+    }
+}
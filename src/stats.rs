@@ -0,0 +1,99 @@
+use std::f64::consts::SQRT_2;
+
+// Distribution functions used by the statistical tests
+
+/// Complementary error function, erfc(x) = 1 - erf(x).
+/// Chebyshev-fitted approximation (Numerical Recipes), fractional error below 1.2e-7.
+pub fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let poly = -z * z - 1.26551223
+        + t * (1.00002368
+        + t * (0.37409196
+        + t * (0.09678418
+        + t * (-0.18628806
+        + t * (0.27886807
+        + t * (-1.13520398
+        + t * (1.48851587
+        + t * (-0.82215223
+        + t * 0.17087277))))))));
+    let ans = t * poly.exp();
+    if x >= 0.0 { ans } else { 2.0 - ans }
+}
+
+/// Standard normal cumulative distribution function
+pub fn normal_cdf(x: f64) -> f64 {
+    0.5 * erfc(-x / SQRT_2)
+}
+
+/// Natural log of the gamma function for x > 0 (Lanczos approximation, g = 7)
+pub fn ln_gamma(x: f64) -> f64 {
+    const COEF: [f64; 9] = [
+        0.999_999_999_999_809_9, 676.5203681218851, -1259.1392167224028,
+        771.323_428_777_653_1, -176.615_029_162_140_6, 12.507343278686905,
+        -0.13857109526572012, 9.984_369_578_019_572e-6, 1.5056327351493116e-7,
+    ];
+
+    if x < 0.5 {
+        // Reflection formula
+        let pi = std::f64::consts::PI;
+        return (pi / (pi * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+
+    let x = x - 1.0;
+    let t = x + 7.5;
+    let series = COEF[1..].iter().enumerate()
+        .fold(COEF[0], |acc, (i, c)| acc + c / (x + i as f64 + 1.0));
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + series.ln()
+}
+
+/// Regularized upper incomplete gamma function Q(a, x) = Γ(a, x) / Γ(a), for a > 0 and x >= 0.
+///
+/// Series expansion below x = a + 1, continued fraction (modified Lentz) above it.
+pub fn gamma_q(a: f64, x: f64) -> f64 {
+    assert!(a > 0.0 && x >= 0.0, "gamma_q needs a > 0 and x >= 0, got a = {}, x = {}", a, x);
+    const EPS: f64 = 1e-14;
+    const MAX_ITER: usize = 1000;
+
+    if x == 0.0 { return 1.0; }
+    let log_prefix = a * x.ln() - x - ln_gamma(a);
+
+    if x < a + 1.0 {
+        // P(a, x) by its series, then Q = 1 - P
+        let mut term = 1.0 / a;
+        let mut sum = term;
+        let mut ap = a;
+        for _ in 0..MAX_ITER {
+            ap += 1.0;
+            term *= x / ap;
+            sum += term;
+            if term.abs() < sum.abs() * EPS { break; }
+        }
+        (1.0 - sum * log_prefix.exp()).max(0.0)
+    } else {
+        let tiny = f64::MIN_POSITIVE / EPS;
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / tiny;
+        let mut d = 1.0 / b;
+        let mut h = d;
+        for i in 1..MAX_ITER {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            if d.abs() < tiny { d = tiny; }
+            c = b + an / c;
+            if c.abs() < tiny { c = tiny; }
+            d = 1.0 / d;
+            let delta = d * c;
+            h *= delta;
+            if (delta - 1.0).abs() < EPS { break; }
+        }
+        (log_prefix.exp() * h).min(1.0)
+    }
+}
+
+/// Survival function P(X > x) of the chi-square distribution with `df` degrees of freedom
+pub fn chi2_sf(x: f64, df: f64) -> f64 {
+    if x <= 0.0 { return 1.0; }
+    gamma_q(df / 2.0, x / 2.0)
+}
@@ -0,0 +1,101 @@
+use std::fmt;
+use crate::dag::Variable;
+
+/// One step of a discovery run, in the order it happened
+#[derive(Debug, Clone, PartialEq)]
+pub enum TraceEvent {
+    /// A conditional independence test of X and Y given `conditioning_set`
+    CITest {
+        x: Variable,
+        y: Variable,
+        conditioning_set: Vec<Variable>,
+        statistic: f64,
+        p_value: f64,
+        independent: bool,
+    },
+    /// X - Y was removed from the skeleton; `sepset` is `None` if background knowledge forbade it
+    EdgeRemoved { x: Variable, y: Variable, sepset: Option<Vec<Variable>> },
+    /// An edge oriented by background knowledge
+    Knowledge { from: Variable, to: Variable },
+    /// X -> Y <- Z
+    Collider { x: Variable, y: Variable, z: Variable },
+    /// A collider that contradicted an earlier one; the triple is marked ambiguous
+    ConflictingCollider { x: Variable, y: Variable, z: Variable },
+    /// A collider that background knowledge rules out, left unoriented
+    ForbiddenCollider { x: Variable, y: Variable, z: Variable },
+    /// The separating sets disagree on whether X - Y - Z is a collider
+    AmbiguousTriple { x: Variable, y: Variable, z: Variable },
+    /// Meek rule `rule` oriented `from` -> `to`
+    MeekRule { rule: u8, from: Variable, to: Variable },
+    /// The run hit `limit`; the search stops early from here on
+    Truncated { limit: Limit },
+}
+
+impl fmt::Display for TraceEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TraceEvent::CITest { x, y, conditioning_set, statistic, p_value, independent } => write!(
+                f, "CI Test: {} _||_ {} | {:?}: statistic {:.4}, p = {:.4} ({})",
+                x, y, conditioning_set, statistic, p_value, if *independent { "independent" } else { "dependent" }
+            ),
+            TraceEvent::EdgeRemoved { x, y, sepset: Some(sepset) } => write!(f, "Removed Edge: {} - {} given {:?}", x, y, sepset),
+            TraceEvent::EdgeRemoved { x, y, sepset: None } => write!(f, "Removed Edge: {} - {} (background knowledge)", x, y),
+            TraceEvent::Knowledge { from, to } => write!(f, "Background Knowledge: {} -> {}", from, to),
+            TraceEvent::Collider { x, y, z } => write!(f, "Oriented Collider: {} -> {} <- {}", x, y, z),
+            TraceEvent::ConflictingCollider { x, y, z } => write!(f, "Conflicting Collider: {} -> {} <- {}", x, y, z),
+            TraceEvent::ForbiddenCollider { x, y, z } => write!(f, "Collider Forbidden by Background Knowledge: {} -> {} <- {}", x, y, z),
+            TraceEvent::AmbiguousTriple { x, y, z } => write!(f, "Ambiguous Triple: {} - {} - {}", x, y, z),
+            TraceEvent::MeekRule { rule, from, to } => write!(f, "Meek Rule {}: {} -> {}", rule, from, to),
+            TraceEvent::Truncated { limit } => write!(f, "Search Truncated: {:?} limit reached", limit),
+        }
+    }
+}
+
+/// The limit that cut a discovery run short
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    /// Larger conditioning sets were still possible
+    Depth,
+    /// The maximum number of CI tests was reached
+    Tests,
+    /// The time budget ran out
+    Time,
+}
+
+/// Callback receiving each trace event as it happens.
+///
+/// It must be `Send + Sync` because the parallel skeleton search (the `parallel` feature) shares
+/// the PC run, observer included, with its worker threads; events are still reported in order
+/// from the calling thread. The bound holds whether or not the feature is on.
+pub type Observer = Box<dyn Fn(&TraceEvent) + Send + Sync>;
+
+/// Everything a discovery run did, e.g. `PC::trace` after `PC::run`
+#[derive(Debug, Clone, Default)]
+pub struct Trace {
+    pub events: Vec<TraceEvent>,
+    /// Number of CI tests run, including any left out of `events`
+    pub ci_test_count: usize,
+}
+
+impl Trace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, event: TraceEvent) {
+        if matches!(event, TraceEvent::CITest { .. }) {
+            self.ci_test_count += 1;
+        }
+        self.events.push(event);
+    }
+
+    /// The conditional independence tests, in the order they were run
+    pub fn ci_tests(&self) -> impl Iterator<Item = &TraceEvent> {
+        self.events.iter().filter(|e| matches!(e, TraceEvent::CITest { .. }))
+    }
+
+    /// Every orientation step: background knowledge, colliders and Meek rules
+    pub fn orientations(&self) -> impl Iterator<Item = &TraceEvent> {
+        self.events.iter().filter(|e| !matches!(e, TraceEvent::CITest { .. } | TraceEvent::EdgeRemoved { .. } | TraceEvent::Truncated { .. }))
+    }
+}
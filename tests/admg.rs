@@ -0,0 +1,88 @@
+use std::fs::File;
+use std::io::Write;
+use why_rs::admg::ADMG;
+use why_rs::dag::{Variable, DAG};
+use why_rs::dag;
+use why_rs::identification::identify_admg;
+
+fn vars(names: &[&str]) -> Vec<Variable> {
+    names.iter().map(|n| Variable::from(*n)).collect()
+}
+
+#[test]
+fn test_from_dot_reads_bidirected_edges() {
+    let file_path = "test_admg.dot";
+    let dot_content = r#"
+    digraph G {
+        X -> M -> Y;
+        X -> Y [dir=both];
+        M -> W [style=dashed];
+    }
+    "#;
+
+    {
+        let mut file = File::create(file_path).expect("Failed to create temp test file");
+        file.write_all(dot_content.as_bytes()).expect("Failed to write to temp test file");
+    }
+
+    let admg = ADMG::from_dot(file_path).expect("ADMG::from_dot failed to load file");
+    let _ = std::fs::remove_file(file_path);
+
+    assert_eq!(admg.get_parents(&Variable::from("Y")), vars(&["M"]));
+    assert_eq!(admg.get_siblings(&Variable::from("Y")), vars(&["X"]));
+    assert_eq!(admg.get_siblings(&Variable::from("W")), vars(&["M"]));
+    assert!(admg.get_parents(&Variable::from("W")).is_empty());
+}
+
+#[test]
+fn test_sort_and_districts_ignore_bidirected_direction() {
+    // Y <-> X is stored "backwards" but must not constrain the order
+    let admg = ADMG::new()
+        .node("X").node("M").node("Y")
+        .edge("X", "M")
+        .edge("M", "Y")
+        .bidirected("Y", "X");
+
+    assert_eq!(admg.sort(), vars(&["X", "M", "Y"]));
+    assert_eq!(admg.districts(), vec![vars(&["X", "Y"]), vars(&["M"])]);
+
+    let shown = admg.to_string();
+    assert!(shown.contains("X <-> [\"Y\"]"), "{}", shown);
+}
+
+#[test]
+fn test_latent_projection_and_identification() {
+    let dag: DAG = dag!(
+        "U" => "X",
+        "U" => "Y",
+        "X" => "M",
+        "M" => "Y"
+    );
+    let admg = ADMG::latent_projection(&dag, &vars(&["U"])).unwrap();
+
+    assert!(admg.get_index(&Variable::from("U")).is_none());
+    assert!(admg.has_bidirected(&Variable::from("X"), &Variable::from("Y")));
+    assert_eq!(admg.get_parents(&Variable::from("Y")), vars(&["M"]));
+
+    let estimand = identify_admg(&admg, &vars(&["Y"]), &vars(&["X"])).unwrap();
+    assert_eq!(estimand.to_string(), "Σ_{M} P(M | X) [Σ_{X} P(X) P(Y | M, X)]");
+}
+
+#[test]
+fn test_m_separation() {
+    // X -> M -> Y with X <-> Y
+    let admg = ADMG::new()
+        .node("X").node("M").node("Y")
+        .edge("X", "M")
+        .edge("M", "Y")
+        .bidirected("X", "Y");
+
+    assert!(!admg.is_m_separated(&vars(&["X"]), &vars(&["Y"]), &vars(&["M"])).unwrap());
+    // Without the bidirected edge M blocks the only path
+    let dag_only = ADMG::from_dag(&admg.directed_part());
+    assert!(dag_only.is_m_separated(&vars(&["X"]), &vars(&["Y"]), &vars(&["M"])).unwrap());
+
+    let (canonical, latent) = admg.canonical_dag();
+    assert_eq!(latent.len(), 1);
+    assert_eq!(canonical.get_children(&latent[0]).len(), 2);
+}
@@ -0,0 +1,66 @@
+use why_rs::backdoor::{is_valid_adjustment_set, minimal_adjustment_sets, optimal_adjustment_set};
+use why_rs::dag::{Variable, DAG};
+use why_rs::dag;
+
+fn vars(names: &[&str]) -> Vec<Variable> {
+    names.iter().map(|n| Variable::from(*n)).collect()
+}
+
+fn confounded() -> DAG {
+    // Z1 -> X, Z1 -> Z2 <- Z3, Z3 -> Y, Z2 -> X, Z2 -> Y, X -> M -> Y
+    dag!(
+        "Z1" => "X",
+        "Z1" => "Z2",
+        "Z3" => "Z2",
+        "Z3" => "Y",
+        "Z2" => "X",
+        "Z2" => "Y",
+        "X" => "M",
+        "M" => "Y"
+    )
+}
+
+#[test]
+fn test_backdoor_validity() {
+    let dag = confounded();
+    let x = Variable::from("X");
+    let y = Variable::from("Y");
+
+    assert!(!is_valid_adjustment_set(&dag, &x, &y, &[]).unwrap());
+    assert!(is_valid_adjustment_set(&dag, &x, &y, &vars(&["Z2", "Z3"])).unwrap());
+    assert!(is_valid_adjustment_set(&dag, &x, &y, &vars(&["Z1", "Z2"])).unwrap());
+    // Conditioning on the collider Z2 alone opens Z1 -> Z2 <- Z3
+    assert!(!is_valid_adjustment_set(&dag, &x, &y, &vars(&["Z2"])).unwrap());
+    // Mediators are descendants of the treatment
+    assert!(!is_valid_adjustment_set(&dag, &x, &y, &vars(&["Z1", "Z2", "M"])).unwrap());
+}
+
+#[test]
+fn test_minimal_sets() {
+    let dag = confounded();
+    let sets = minimal_adjustment_sets(&dag, &Variable::from("X"), &Variable::from("Y")).unwrap();
+
+    assert_eq!(sets, vec![vars(&["Z1", "Z2"]), vars(&["Z2", "Z3"])]);
+}
+
+#[test]
+fn test_minimal_set_is_empty_without_confounding() {
+    let dag: DAG = dag!("X" => "Y", "Y" => "W", "V" => "W");
+    let sets = minimal_adjustment_sets(&dag, &Variable::from("X"), &Variable::from("Y")).unwrap();
+
+    assert_eq!(sets, vec![Vec::<Variable>::new()]);
+}
+
+#[test]
+fn test_optimal_set() {
+    let dag = confounded();
+    let x = Variable::from("X");
+    let y = Variable::from("Y");
+
+    let o_set = optimal_adjustment_set(&dag, &x, &y).unwrap().unwrap();
+    assert_eq!(o_set, vars(&["Z2", "Z3"]));
+    assert!(is_valid_adjustment_set(&dag, &x, &y, &o_set).unwrap());
+
+    // No causal path from Y back to X
+    assert_eq!(optimal_adjustment_set(&dag, &y, &x).unwrap(), None);
+}
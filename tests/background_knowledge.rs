@@ -0,0 +1,74 @@
+mod common;
+
+use common::{variables, Oracle};
+use polars::prelude::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rand_distr::{Distribution, Normal};
+use why_rs::independence::FisherZ;
+use why_rs::knowledge::BackgroundKnowledge;
+use why_rs::pc::PC;
+
+// A -> B -> C, linear Gaussian
+fn chain(n: usize, seed: u64) -> DataFrame {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let noise = Normal::new(0.0, 1.0).unwrap();
+    let a: Vec<f64> = (0..n).map(|_| noise.sample(&mut rng)).collect();
+    let b: Vec<f64> = a.iter().map(|a| 0.8 * a + noise.sample(&mut rng)).collect();
+    let c: Vec<f64> = b.iter().map(|b| 0.8 * b + noise.sample(&mut rng)).collect();
+
+    DataFrame::new(vec![
+        Column::from(Series::new(PlSmallStr::from("A"), a)),
+        Column::from(Series::new(PlSmallStr::from("B"), b)),
+        Column::from(Series::new(PlSmallStr::from("C"), c)),
+    ]).unwrap()
+}
+
+#[test]
+fn test_tiers_orient_a_markov_equivalent_chain() {
+    let df = chain(1000, 1);
+
+    let mut pc = PC::new(variables(&["A", "B", "C"]), FisherZ::new(&df));
+    pc.run(0.01);
+    assert!(pc.graph.is_undirected("A", "B") && pc.graph.is_undirected("B", "C"));
+
+    let knowledge = BackgroundKnowledge::new().tier(0, &["A"]).tier(1, &["B", "C"]);
+    let mut pc = PC::new(variables(&["A", "B", "C"]), FisherZ::new(&df)).knowledge(knowledge);
+    pc.run(0.01);
+    // A -> B from the tiers, B -> C from Meek rule 1
+    assert!(pc.graph.is_directed("A", "B"));
+    assert!(pc.graph.is_directed("B", "C"));
+}
+
+#[test]
+fn test_forbidden_and_required_edges() {
+    let df = chain(1000, 2);
+
+    // A and C are dependent, but the edge is forbidden; with no sepset B is not a collider
+    let knowledge = BackgroundKnowledge::new().forbid("A", "C").forbid("C", "A").require("C", "B");
+    let mut pc = PC::new(variables(&["A", "B", "C"]), FisherZ::new(&df)).knowledge(knowledge);
+    pc.run(0.01);
+    assert!(!pc.graph.is_adjacent("A", "C"));
+    assert!(pc.sepsets.is_empty());
+    assert!(pc.graph.is_directed("C", "B"));
+    // Meek rule 1 propagates the required edge
+    assert!(pc.graph.is_directed("B", "A"));
+
+    // Independent variables stay joined by a required edge
+    let knowledge = BackgroundKnowledge::new().require("X", "Y");
+    let mut pc = PC::new(variables(&["X", "Y"]), Oracle(|_, _, _| true)).knowledge(knowledge);
+    pc.run(0.05);
+    assert!(pc.graph.is_directed("X", "Y"));
+}
+
+#[test]
+fn test_tier_order_blocks_colliders() {
+    // A -> C <- B from the data, but C is known to precede A
+    let knowledge = BackgroundKnowledge::new().tier(0, &["C"]).tier(1, &["A", "B"]);
+    let mut pc = PC::new(variables(&["A", "B", "C"]), Oracle(|a, b, z| (a, b) == ("A", "B") && z.is_empty()))
+        .knowledge(knowledge);
+    pc.run(0.05);
+
+    assert!(pc.graph.is_directed("C", "A"));
+    assert!(pc.graph.is_directed("C", "B"));
+}
@@ -0,0 +1,79 @@
+mod common;
+
+use std::cell::Cell;
+use common::{edges, simulate, simulate_uniform, variables};
+use polars::prelude::DataFrame;
+use why_rs::bootstrap::Bootstrap;
+use why_rs::independence::FisherZ;
+use why_rs::lingam::DirectLiNGAM;
+use why_rs::pc::PC;
+use why_rs::pdag::PDAG;
+
+fn pc_learner(df: &DataFrame) -> PDAG {
+    let mut pc = PC::new(variables(&["A", "B", "C", "D"]), FisherZ::new(df));
+    pc.run(0.01);
+    pc.graph
+}
+
+#[test]
+fn test_bootstrap_pc_frequencies_and_consensus() {
+    let (truth, df) = simulate(&[("A", "C", 0.8), ("B", "C", 0.8), ("C", "D", 0.8)], 500, 31);
+
+    let stability = Bootstrap::new(20).seed(7).run(&df, pc_learner);
+
+    let ac = stability.edge("A", "C");
+    assert_eq!(ac.absent, 0.0);
+    assert!(ac.forward >= 0.7, "{:?}", ac);
+    assert!((ac.absent + ac.forward + ac.backward + ac.undirected - 1.0).abs() < 1e-12);
+    // Asking for the reversed pair swaps the orientations
+    assert_eq!(stability.edge("C", "A").backward, ac.forward);
+    assert!(stability.edge("A", "B").absent >= 0.7);
+
+    assert_eq!(edges(&stability.consensus(0.5)), edges(&PDAG::from_dag(&truth)));
+
+    // Same seed, same resamples
+    let again = Bootstrap::new(20).seed(7).run(&df, pc_learner);
+    assert_eq!(stability.edges(), again.edges());
+}
+
+#[test]
+fn test_bootstrap_any_learner() {
+    // A DAG learner plugs in by converting its result to a PDAG
+    let (_, df) = simulate_uniform(&[("X", "Y", 1.0)], 1000, 32);
+    let stability = Bootstrap::new(10).seed(1).run(&df, |resample| {
+        let mut lingam = DirectLiNGAM::new(vec!["X".to_string(), "Y".to_string()], resample);
+        lingam.run(0.01);
+        let mut graph = PDAG::new().node("X").node("Y");
+        for (from, to) in lingam.graph.weights.keys() {
+            graph.add_directed(from, to);
+        }
+        graph
+    });
+
+    assert!(stability.edge("X", "Y").forward >= 0.9);
+    assert!(stability.consensus(0.9).is_directed("X", "Y"));
+}
+
+#[test]
+fn test_consensus_prefers_more_frequent_orientation() {
+    // B -> A in two of every three resamples, A -> B in the other
+    let (_, df) = simulate(&[("A", "B", 1.0)], 50, 33);
+    let calls = Cell::new(0);
+    let stability = Bootstrap::new(3).run(&df, |_| {
+        calls.set(calls.get() + 1);
+        let mut graph = PDAG::new().node("A").node("B");
+        if calls.get() % 3 == 0 { graph.add_directed("A", "B"); } else { graph.add_directed("B", "A"); }
+        graph
+    });
+
+    // Both orientations reach a low threshold; the more frequent one wins
+    assert!(stability.consensus(0.3).is_directed("B", "A"));
+    // A tie is left undirected
+    let tie = Bootstrap::new(2).run(&df, |_| {
+        calls.set(calls.get() + 1);
+        let mut graph = PDAG::new().node("A").node("B");
+        if calls.get() % 2 == 0 { graph.add_directed("A", "B"); } else { graph.add_directed("B", "A"); }
+        graph
+    });
+    assert!(tie.consensus(0.3).is_undirected("A", "B"));
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use common::{variables, Oracle};
+use why_rs::pc::{ColliderRule, PC};
+
+fn triple(x: &str, y: &str, z: &str) -> (String, String, String) {
+    (x.to_string(), y.to_string(), z.to_string())
+}
+
+// A and C are separated by any non-empty subset of {B, D}; everything else is dependent.
+// Skeleton A - B - C, A - D - C, B - D
+fn split_votes(a: &str, b: &str, z: &[String]) -> bool {
+    (a, b) == ("A", "C") && !z.is_empty()
+}
+
+#[test]
+fn test_rules_disagree_on_split_votes() {
+    // The first sepset found is {B}, so only A - D - C becomes a collider
+    let mut standard = PC::new(variables(&["A", "B", "C", "D"]), Oracle(split_votes));
+    standard.run(0.05);
+    assert!(standard.graph.is_directed("A", "D") && standard.graph.is_directed("C", "D"));
+    assert!(standard.graph.is_undirected("A", "B"));
+    assert!(standard.ambiguous_triples.is_empty());
+
+    // B and D are each in 2 of the 3 separating sets
+    let mut majority = PC::new(variables(&["A", "B", "C", "D"]), Oracle(split_votes))
+        .collider_rule(ColliderRule::Majority);
+    majority.run(0.05);
+    assert!(majority.graph.directed_edges().is_empty());
+    assert!(majority.ambiguous_triples.is_empty());
+
+    let mut conservative = PC::new(variables(&["A", "B", "C", "D"]), Oracle(split_votes))
+        .collider_rule(ColliderRule::Conservative);
+    conservative.run(0.05);
+    assert!(conservative.graph.directed_edges().is_empty());
+    assert_eq!(conservative.ambiguous_triples, vec![triple("A", "B", "C"), triple("A", "D", "C")]);
+}
+
+#[test]
+fn test_conflicting_colliders_are_not_overwritten() {
+    // A - B - C - D with colliders A -> B <- C and B -> C <- D both suggested
+    let mut pc = PC::new(variables(&["A", "B", "C", "D"]), Oracle(|a, b, z| match (a, b) {
+        ("A", "C") | ("B", "D") => z.is_empty(),
+        ("A", "D") => true,
+        _ => false,
+    }));
+    pc.run(0.05);
+
+    assert!(pc.graph.is_directed("A", "B"));
+    assert!(pc.graph.is_directed("C", "B"));
+    assert!(pc.graph.is_directed("D", "C"));
+    assert_eq!(pc.ambiguous_triples, vec![triple("B", "C", "D")]);
+}
+
+#[test]
+fn test_meek_rules_skip_ambiguous_triples() {
+    // W -> A <- C is a clear collider; C and W are independent of B given both {} and {A}
+    let oracle = Oracle(|a, b, z| match (a, b) {
+        ("C", "W") => !z.contains(&"A".to_string()),
+        ("B", "C") | ("B", "W") => true,
+        _ => false,
+    });
+    let mut pc = PC::new(variables(&["A", "B", "C", "W"]), oracle).collider_rule(ColliderRule::Conservative);
+    pc.run(0.05);
+
+    assert!(pc.graph.is_directed("W", "A") && pc.graph.is_directed("C", "A"));
+    assert_eq!(pc.ambiguous_triples, vec![triple("B", "A", "C"), triple("B", "A", "W")]);
+    // Rule 1 would have oriented A -> B
+    assert!(pc.graph.is_undirected("A", "B"));
+}
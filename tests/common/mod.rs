@@ -0,0 +1,96 @@
+// Fixtures shared by the integration tests; not every test binary uses all of them
+#![allow(dead_code)]
+
+use polars::prelude::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rand_distr::{Distribution, Normal, Uniform};
+use why_rs::dag::{Variable, DAG};
+use why_rs::independence::{CITest, CITestResult};
+use why_rs::pdag::PDAG;
+
+// Linear Gaussian data generated by the weighted edges `(from, to, weight)`
+pub fn simulate(edges: &[(&str, &str, f64)], n: usize, seed: u64) -> (DAG, DataFrame) {
+    simulate_with_noise(edges, n, seed, true)
+}
+
+// Like `simulate`, with uniform noise on [-1, 1] (non-Gaussian, as LiNGAM needs)
+pub fn simulate_uniform(edges: &[(&str, &str, f64)], n: usize, seed: u64) -> (DAG, DataFrame) {
+    simulate_with_noise(edges, n, seed, false)
+}
+
+fn simulate_with_noise(edges: &[(&str, &str, f64)], n: usize, seed: u64, gaussian: bool) -> (DAG, DataFrame) {
+    let mut dag = DAG::new();
+    for (from, to, _) in edges {
+        dag = dag.node(from).node(to).edge(from, to);
+    }
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let uniform = Uniform::new(-1.0, 1.0);
+    let normal = Normal::new(0.0, 1.0).unwrap();
+    let mut columns: Vec<(Variable, Vec<f64>)> = Vec::new();
+
+    for node in dag.sort() {
+        let incoming: Vec<(&Vec<f64>, f64)> = edges.iter()
+            .filter(|(_, to, _)| *to == node)
+            .map(|(from, _, w)| (&columns.iter().find(|(name, _)| name == from).unwrap().1, *w))
+            .collect();
+        let values: Vec<f64> = (0..n)
+            .map(|i| {
+                let noise = if gaussian { normal.sample(&mut rng) } else { uniform.sample(&mut rng) };
+                incoming.iter().map(|(col, w)| w * col[i]).sum::<f64>() + noise
+            })
+            .collect();
+        columns.push((node, values));
+    }
+
+    let df = DataFrame::new(columns.into_iter()
+        .map(|(name, values)| Column::from(Series::new(PlSmallStr::from(name), values)))
+        .collect()).unwrap();
+    (dag, df)
+}
+
+// Sorted directed edges and the number of undirected ones
+pub fn sorted_edges(pdag: &PDAG) -> (Vec<(Variable, Variable)>, usize) {
+    let mut directed = pdag.directed_edges();
+    directed.sort();
+    (directed, pdag.undirected_edges().len())
+}
+
+pub type EdgeList = Vec<(Variable, Variable)>;
+
+// Sorted directed and undirected edges, undirected ones as (A, B) with A < B
+pub fn edges(pdag: &PDAG) -> (EdgeList, EdgeList) {
+    let mut directed = pdag.directed_edges();
+    directed.sort();
+    let mut undirected: EdgeList = pdag.undirected_edges()
+        .into_iter()
+        .map(|(a, b)| if a < b { (a, b) } else { (b, a) })
+        .collect();
+    undirected.sort();
+    (directed, undirected)
+}
+
+// Sorted edges of a DAG
+pub fn dag_edges(dag: &DAG) -> Vec<(Variable, Variable)> {
+    let mut edges: Vec<(Variable, Variable)> = dag.variables().into_iter()
+        .flat_map(|v| dag.get_parents(&v).into_iter().map(move |p| (p, v.clone())))
+        .collect();
+    edges.sort();
+    edges
+}
+
+pub fn variables(names: &[&str]) -> Vec<Variable> {
+    names.iter().map(|v| v.to_string()).collect()
+}
+
+// Answers independence queries from a hand-written rule instead of data
+pub struct Oracle(pub fn(&str, &str, &[String]) -> bool);
+
+impl CITest for Oracle {
+    fn test(&self, x: &str, y: &str, z: &[String]) -> CITestResult {
+        let (a, b) = if x < y { (x, y) } else { (y, x) };
+        let p_value = if (self.0)(a, b, z) { 0.9 } else { 0.0 };
+        CITestResult { statistic: 0.0, p_value }
+    }
+}
@@ -0,0 +1,63 @@
+use why_rs::dag::{DagError, Variable, DAG};
+use why_rs::dag;
+
+fn vars(names: &[&str]) -> Vec<Variable> {
+    names.iter().map(|n| Variable::from(*n)).collect()
+}
+
+#[test]
+fn test_chain_and_fork_blocked_by_middle_node() {
+    // Chain: A -> B -> C
+    let chain: DAG = dag!("A" => "B", "B" => "C");
+    assert!(!chain.is_d_separated(&vars(&["A"]), &vars(&["C"]), &[]).unwrap());
+    assert!(chain.is_d_separated(&vars(&["A"]), &vars(&["C"]), &vars(&["B"])).unwrap());
+
+    // Fork: A <- B -> C
+    let fork: DAG = dag!("B" => "A", "B" => "C");
+    assert!(!fork.is_d_separated(&vars(&["A"]), &vars(&["C"]), &[]).unwrap());
+    assert!(fork.is_d_separated(&vars(&["A"]), &vars(&["C"]), &vars(&["B"])).unwrap());
+}
+
+#[test]
+fn test_collider_opened_by_descendant() {
+    // A -> C <- B, C -> D
+    let dag: DAG = dag!(
+        "A" => "C",
+        "B" => "C",
+        "C" => "D"
+    );
+
+    assert!(dag.is_d_separated(&vars(&["A"]), &vars(&["B"]), &[]).unwrap());
+    assert!(!dag.is_d_separated(&vars(&["A"]), &vars(&["B"]), &vars(&["C"])).unwrap());
+    assert!(!dag.is_d_separated(&vars(&["A"]), &vars(&["B"]), &vars(&["D"])).unwrap());
+}
+
+#[test]
+fn test_sets_of_variables() {
+    // Classic "M-bias" shape: X <- U1 -> M <- U2 -> Y, plus X -> Y
+    let dag: DAG = dag!(
+        "U1" => "X",
+        "U1" => "M",
+        "U2" => "M",
+        "U2" => "Y",
+        "X" => "Y"
+    );
+
+    assert!(dag.is_d_separated(&vars(&["U1"]), &vars(&["U2"]), &[]).unwrap());
+    assert!(!dag.is_d_separated(&vars(&["U1"]), &vars(&["U2"]), &vars(&["M"])).unwrap());
+    assert!(!dag.is_d_separated(&vars(&["X", "U1"]), &vars(&["Y"]), &vars(&["M"])).unwrap());
+}
+
+#[test]
+fn test_unknown_and_overlapping_variables_are_errors() {
+    let dag: DAG = dag!("A" => "B");
+
+    assert_eq!(
+        dag.is_d_separated(&vars(&["A"]), &vars(&["Z"]), &[]),
+        Err(DagError::UnknownVariable(Variable::from("Z")))
+    );
+    assert_eq!(
+        dag.is_d_separated(&vars(&["A"]), &vars(&["B"]), &vars(&["A"])),
+        Err(DagError::OverlappingSets(Variable::from("A")))
+    );
+}
@@ -0,0 +1,95 @@
+use polars::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use why_rs::independence::{CITest, ChiSquare, GSquare};
+use why_rs::pc::PC;
+use why_rs::stats::{chi2_sf, ln_gamma};
+
+// Copies `bit`, flipping it with probability `flip`
+fn noisy(rng: &mut StdRng, bit: bool, flip: f64) -> bool {
+    if rng.gen_bool(flip) { !bit } else { bit }
+}
+
+// A -> B -> C with binary variables, stored as integers, strings and booleans
+fn chain(n: usize, seed: u64) -> DataFrame {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let a: Vec<bool> = (0..n).map(|_| rng.gen_bool(0.5)).collect();
+    let b: Vec<bool> = a.iter().map(|&a| noisy(&mut rng, a, 0.2)).collect();
+    let c: Vec<bool> = b.iter().map(|&b| noisy(&mut rng, b, 0.2)).collect();
+
+    DataFrame::new(vec![
+        Column::from(Series::new(PlSmallStr::from("A"), a.iter().map(|&a| a as i32).collect::<Vec<i32>>())),
+        Column::from(Series::new(PlSmallStr::from("B"), b.iter().map(|&b| if b { "yes" } else { "no" }).collect::<Vec<&str>>())),
+        Column::from(Series::new(PlSmallStr::from("C"), c)),
+    ]).unwrap()
+}
+
+// A -> C <- B with C a noisy OR of A and B
+fn collider(n: usize, seed: u64) -> DataFrame {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let a: Vec<bool> = (0..n).map(|_| rng.gen_bool(0.5)).collect();
+    let b: Vec<bool> = (0..n).map(|_| rng.gen_bool(0.5)).collect();
+    let c: Vec<bool> = a.iter().zip(&b).map(|(&a, &b)| noisy(&mut rng, a || b, 0.1)).collect();
+
+    DataFrame::new(vec![
+        Column::from(Series::new(PlSmallStr::from("A"), a.iter().map(|&a| a as i64).collect::<Vec<i64>>())),
+        Column::from(Series::new(PlSmallStr::from("B"), b.iter().map(|&b| b as i64).collect::<Vec<i64>>())),
+        Column::from(Series::new(PlSmallStr::from("C"), c.iter().map(|&c| c as i64).collect::<Vec<i64>>())),
+    ]).unwrap()
+}
+
+fn variables() -> Vec<String> {
+    vec!["A".to_string(), "B".to_string(), "C".to_string()]
+}
+
+#[test]
+fn test_chi2_survival_function() {
+    assert!((chi2_sf(3.841459, 1.0) - 0.05).abs() < 1e-6);
+    assert!((chi2_sf(5.991465, 2.0) - 0.05).abs() < 1e-6);
+    assert!((chi2_sf(18.307038, 10.0) - 0.05).abs() < 1e-6);
+    assert!((chi2_sf(2.0, 4.0) - 0.735759).abs() < 1e-6);
+    assert_eq!(chi2_sf(0.0, 3.0), 1.0);
+    assert!((ln_gamma(5.0) - 24f64.ln()).abs() < 1e-10);
+    assert!((ln_gamma(0.5) - std::f64::consts::PI.sqrt().ln()).abs() < 1e-10);
+}
+
+#[test]
+fn test_discrete_tests_on_mixed_column_types() {
+    let df = chain(2000, 3);
+    let g2 = GSquare::new(&df);
+    let chi2 = ChiSquare::new(&df);
+
+    for test in [&g2 as &dyn CITest, &chi2] {
+        assert!(test.test("A", "C", &[]).p_value < 0.01);
+        assert!(test.test("A", "C", &["B".to_string()]).p_value > 0.05);
+    }
+
+    // The two statistics agree asymptotically
+    let (g, c) = (g2.test("A", "B", &[]), chi2.test("A", "B", &[]));
+    assert!((g.statistic - c.statistic).abs() / c.statistic < 0.1);
+}
+
+#[test]
+fn test_constant_column_is_independent() {
+    let df = DataFrame::new(vec![
+        Column::from(Series::new(PlSmallStr::from("X"), vec![1, 1, 1, 1])),
+        Column::from(Series::new(PlSmallStr::from("Y"), vec![0, 1, 0, 1])),
+    ]).unwrap();
+    let result = GSquare::new(&df).test("X", "Y", &[]);
+    assert_eq!(result.p_value, 1.0);
+}
+
+#[test]
+fn test_pc_with_g_square() {
+    let mut pc = PC::new(variables(), GSquare::new(&chain(2000, 3)));
+    pc.run(0.05);
+    assert!(pc.graph.is_undirected("A", "B"));
+    assert!(pc.graph.is_undirected("B", "C"));
+    assert!(!pc.graph.is_adjacent("A", "C"));
+
+    let mut pc = PC::new(variables(), ChiSquare::new(&collider(2000, 4)));
+    pc.run(0.05);
+    assert!(pc.graph.is_directed("A", "C"));
+    assert!(pc.graph.is_directed("B", "C"));
+    assert!(!pc.graph.is_adjacent("A", "B"));
+}
@@ -0,0 +1,90 @@
+use why_rs::dag::{Variable, DAG};
+use why_rs::fci::FCI;
+use why_rs::independence::{CITest, CITestResult};
+use why_rs::pag::Mark;
+use why_rs::pag::Mark::{Arrow, Circle, Tail};
+use why_rs::pc::PC;
+
+// Perfect independence test: d-separation in a DAG that may contain unobserved nodes
+struct DSeparation(DAG);
+
+impl CITest for DSeparation {
+    fn test(&self, x: &str, y: &str, z: &[Variable]) -> CITestResult {
+        let separated = self.0.is_d_separated(&[x.to_string()], &[y.to_string()], z).unwrap();
+        CITestResult { statistic: 0.0, p_value: if separated { 1.0 } else { 0.0 } }
+    }
+}
+
+fn learn(edges: &[(&str, &str)], observed: &[&str]) -> Vec<(Variable, Mark, Mark, Variable)> {
+    let mut dag = DAG::new();
+    for (from, to) in edges {
+        dag = dag.node(from).node(to).edge(from, to);
+    }
+    let mut fci = FCI::new(observed.iter().map(|v| v.to_string()).collect(), DSeparation(dag));
+    fci.run(0.05);
+    fci.graph.edges()
+}
+
+fn edge(a: &str, mark_a: Mark, mark_b: Mark, b: &str) -> (Variable, Mark, Mark, Variable) {
+    (a.to_string(), mark_a, mark_b, b.to_string())
+}
+
+#[test]
+fn test_latent_confounder_gives_bidirected_edge() {
+    // A -> C <- L -> D <- B with L hidden
+    let pag = learn(&[("A", "C"), ("L", "C"), ("L", "D"), ("B", "D")], &["A", "B", "C", "D"]);
+    assert_eq!(pag, vec![
+        edge("A", Circle, Arrow, "C"),
+        edge("B", Circle, Arrow, "D"),
+        edge("C", Arrow, Arrow, "D"),
+    ]);
+}
+
+#[test]
+fn test_tails_are_oriented_without_confounding() {
+    // A -> C <- B, C -> D: the edge into D is visibly directed
+    let pag = learn(&[("A", "C"), ("B", "C"), ("C", "D")], &["A", "B", "C", "D"]);
+    assert_eq!(pag, vec![
+        edge("A", Circle, Arrow, "C"),
+        edge("B", Circle, Arrow, "C"),
+        edge("C", Tail, Arrow, "D"),
+    ]);
+}
+
+#[test]
+fn test_discriminating_path() {
+    // T -> A -> G <- B with A and B confounded by a hidden L: <T, A, B, G> discriminates B
+    let pag = learn(&[("T", "A"), ("A", "G"), ("B", "G"), ("L", "A"), ("L", "B")], &["A", "B", "G", "T"]);
+    assert_eq!(pag, vec![
+        edge("A", Arrow, Circle, "B"),
+        edge("A", Tail, Arrow, "G"),
+        edge("A", Arrow, Circle, "T"),
+        edge("B", Tail, Arrow, "G"),
+    ]);
+}
+
+#[test]
+fn test_possible_dsep_removes_edge() {
+    // F and G are only separated by {A, C, D, E}, and neither is adjacent to A or C
+    let edges = [
+        ("A", "C"), ("A", "E"), ("C", "E"), ("D", "F"), ("E", "G"),
+        ("L0", "G"), ("L0", "D"), ("L1", "F"), ("L1", "E"), ("L2", "D"), ("L2", "A"), ("L3", "C"), ("L3", "A"),
+    ];
+    let observed: Vec<Variable> = ["A", "C", "D", "E", "F", "G"].iter().map(|v| v.to_string()).collect();
+    let mut dag = DAG::new();
+    for (from, to) in edges {
+        dag = dag.node(from).node(to).edge(from, to);
+    }
+
+    // The PC skeleton keeps the spurious adjacency
+    let mut pc = PC::new(observed.clone(), DSeparation(dag.clone()));
+    pc.run(0.05);
+    assert!(pc.graph.is_adjacent("F", "G"));
+
+    let mut fci = FCI::new(observed, DSeparation(dag));
+    fci.run(0.05);
+    assert!(!fci.graph.is_adjacent("F", "G"));
+    let mut sepset: Vec<Variable> = fci.sepsets[&("F".to_string(), "G".to_string())].iter().cloned().collect();
+    sepset.sort();
+    assert_eq!(sepset, vec!["A", "C", "D", "E"]);
+}
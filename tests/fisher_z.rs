@@ -0,0 +1,218 @@
+mod common;
+
+use common::Oracle;
+use polars::prelude::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use ndarray::{Array1, Array2};
+use rand_distr::{Distribution, Normal};
+use why_rs::independence::{CITest, FisherZ};
+use why_rs::mechanism::LinearRegression;
+use why_rs::pc::PC;
+use why_rs::stats::normal_cdf;
+
+fn weakly_correlated(n: usize, seed: u64) -> DataFrame {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let noise = Normal::new(0.0, 1.0).unwrap();
+
+    let x: Vec<f64> = (0..n).map(|_| noise.sample(&mut rng)).collect();
+    let y: Vec<f64> = x.iter().map(|x| 0.12 * x + noise.sample(&mut rng)).collect();
+
+    DataFrame::new(vec![
+        Column::from(Series::new(PlSmallStr::from("X"), x)),
+        Column::from(Series::new(PlSmallStr::from("Y"), y)),
+    ]).unwrap()
+}
+
+fn variables() -> Vec<String> {
+    vec!["X".to_string(), "Y".to_string()]
+}
+
+#[test]
+fn test_any_alpha_is_honoured() {
+    let df = weakly_correlated(200, 5);
+    let p_value = FisherZ::new(&df).test("X", "Y", &[]).p_value;
+    assert!(p_value > 0.0 && p_value < 1.0);
+
+    // Just above the p-value the edge survives, just below it is removed
+    let mut strict = PC::new(variables(), FisherZ::new(&df));
+    strict.run(p_value * 0.9);
+    assert!(!strict.graph.is_adjacent("X", "Y"));
+
+    let mut loose = PC::new(variables(), FisherZ::new(&df));
+    loose.run((p_value * 1.1).min(0.99));
+    assert!(loose.graph.is_adjacent("X", "Y"));
+}
+
+#[test]
+fn test_sepset_records_statistic_and_p_value() {
+    let df = weakly_correlated(200, 5);
+    let expected = FisherZ::new(&df).test("X", "Y", &[]);
+
+    let mut pc = PC::new(variables(), FisherZ::new(&df));
+    pc.run(expected.p_value / 2.0);
+
+    let recorded = pc.sepset_tests[&("X".to_string(), "Y".to_string())];
+    assert_eq!(recorded, expected);
+    assert!((recorded.p_value - 2.0 * (1.0 - normal_cdf(recorded.statistic))).abs() < 1e-6);
+}
+
+#[test]
+fn test_custom_ci_test() {
+    // X and Y are independent given any set containing Z, everything else is dependent
+    let oracle = Oracle(|a, b, z| (a, b) == ("X", "Y") && z.iter().any(|v| v == "Z"));
+    let variables = vec!["X".to_string(), "Y".to_string(), "Z".to_string()];
+    let mut pc = PC::new(variables, oracle);
+    pc.run(0.05);
+
+    assert!(!pc.graph.is_adjacent("X", "Y"));
+    assert!(pc.graph.is_undirected("X", "Z"));
+    assert!(pc.graph.is_undirected("Y", "Z"));
+    assert_eq!(pc.sepset_tests[&("X".to_string(), "Y".to_string())].p_value, 0.9);
+}
+
+// Pearson correlation of the residuals of X and Y after regressing both on Z
+fn residual_correlation(df: &DataFrame, x: &str, y: &str, z: &[&str]) -> f64 {
+    let column = |name: &str| df.column(name).unwrap().f64().unwrap().into_no_null_iter().collect::<Array1<f64>>();
+    let mut design = Array2::<f64>::zeros((df.height(), z.len()));
+    for (j, name) in z.iter().enumerate() {
+        design.column_mut(j).assign(&column(name));
+    }
+
+    let residuals = |target: &str| {
+        let mut model = LinearRegression::new();
+        model.fit_ndarray(design.clone(), &column(target));
+        column(target) - model.predict(&design)
+    };
+    let (rx, ry) = (residuals(x), residuals(y));
+    rx.dot(&ry) / (rx.dot(&rx) * ry.dot(&ry)).sqrt()
+}
+
+#[test]
+fn test_partial_correlation_matches_regression() {
+    let mut rng = StdRng::seed_from_u64(11);
+    let noise = Normal::new(0.0, 1.0).unwrap();
+    let n = 300;
+
+    let mut columns: Vec<Vec<f64>> = Vec::new();
+    for i in 0..6 {
+        let values: Vec<f64> = (0..n)
+            .map(|row| {
+                let parents: f64 = columns.iter().map(|c: &Vec<f64>| c[row] * 0.4).sum();
+                parents + noise.sample(&mut rng) * (1.0 + i as f64 * 0.1)
+            })
+            .collect();
+        columns.push(values);
+    }
+    let names = ["A", "B", "C", "D", "E", "F"];
+    let df = DataFrame::new(names.iter().zip(&columns)
+        .map(|(name, values)| Column::from(Series::new(PlSmallStr::from(*name), values.clone())))
+        .collect()).unwrap();
+
+    let test = FisherZ::new(&df);
+    let z = ["B", "D", "E"];
+    let conditioning: Vec<String> = z.iter().map(|v| v.to_string()).collect();
+
+    let r = residual_correlation(&df, "A", "F", &z);
+    let expected = r.atanh().abs() * ((n - z.len() - 3) as f64).sqrt();
+    let result = test.test("A", "F", &conditioning);
+    assert!((result.statistic - expected).abs() < 1e-8);
+
+    // Cached inverses give the same answer, whatever the order of X, Y and Z
+    let reordered: Vec<String> = ["E", "B", "D"].iter().map(|v| v.to_string()).collect();
+    assert!((test.test("F", "A", &reordered).statistic - expected).abs() < 1e-8);
+}
+
+#[test]
+fn test_bounded_cache_gives_same_results() {
+    let mut rng = StdRng::seed_from_u64(5);
+    let noise = Normal::new(0.0, 1.0).unwrap();
+    let names = ["A", "B", "C", "D"];
+    let df = DataFrame::new(names.iter()
+        .map(|name| Column::from(Series::new(PlSmallStr::from(*name), (0..200).map(|_| noise.sample(&mut rng)).collect::<Vec<f64>>())))
+        .collect()).unwrap();
+    let vars: Vec<String> = names.iter().map(|v| v.to_string()).collect();
+
+    let unbounded = FisherZ::new(&df).cache_capacity(usize::MAX);
+    let tiny = FisherZ::new(&df).cache_capacity(1);
+    let uncached = FisherZ::new(&df).cache_capacity(0);
+
+    // Alternate between conditioning sets so the tiny cache keeps evicting
+    for _ in 0..2 {
+        for z in [&vars[2..3], &vars[3..4], &vars[2..4]] {
+            let expected = unbounded.test("A", "B", z);
+            assert_eq!(tiny.test("A", "B", z), expected);
+            assert_eq!(uncached.test("A", "B", z), expected);
+        }
+    }
+}
+
+// Sample covariance of the named columns, in order
+fn covariance(df: &DataFrame, names: &[&str]) -> Array2<f64> {
+    let mut x = Array2::<f64>::zeros((df.height(), names.len()));
+    for (j, name) in names.iter().enumerate() {
+        let column: Array1<f64> = df.column(name).unwrap().f64().unwrap().into_no_null_iter().collect();
+        let mean = column.mean().unwrap();
+        x.column_mut(j).assign(&(column - mean));
+    }
+    x.t().dot(&x) / (df.height() - 1) as f64
+}
+
+#[test]
+fn test_pc_from_covariance_matches_raw_data() {
+    // A -> C <- B, C -> D, on very different scales
+    let mut rng = StdRng::seed_from_u64(17);
+    let noise = Normal::new(0.0, 1.0).unwrap();
+    let n = 400;
+    let a: Vec<f64> = (0..n).map(|_| 100.0 * noise.sample(&mut rng)).collect();
+    let b: Vec<f64> = (0..n).map(|_| noise.sample(&mut rng)).collect();
+    let c: Vec<f64> = (0..n).map(|i| 0.01 * a[i] - 0.8 * b[i] + noise.sample(&mut rng)).collect();
+    let d: Vec<f64> = (0..n).map(|i| 5.0 * c[i] + 3.0 * noise.sample(&mut rng)).collect();
+    let df = DataFrame::new(vec![
+        Column::from(Series::new(PlSmallStr::from("A"), a)),
+        Column::from(Series::new(PlSmallStr::from("B"), b)),
+        Column::from(Series::new(PlSmallStr::from("C"), c)),
+        Column::from(Series::new(PlSmallStr::from("D"), d)),
+    ]).unwrap();
+
+    let names = ["A", "B", "C", "D"];
+    let variables: Vec<String> = names.iter().map(|v| v.to_string()).collect();
+    let cov = covariance(&df, &names);
+
+    let from_data = FisherZ::new(&df);
+    let from_cov = FisherZ::from_covariance(variables.clone(), cov.clone(), n);
+    let z = vec!["C".to_string()];
+    for (x, y) in [("A", "B"), ("A", "D"), ("B", "D")] {
+        assert!((from_data.test(x, y, &z).p_value - from_cov.test(x, y, &z).p_value).abs() < 1e-10);
+    }
+
+    // The correlation matrix is equivalent
+    let std = cov.diag().mapv(f64::sqrt);
+    let corr = Array2::from_shape_fn((4, 4), |(i, j)| cov[[i, j]] / (std[i] * std[j]));
+
+    let mut expected = PC::new(variables.clone(), FisherZ::new(&df));
+    expected.run(0.01);
+    for mut pc in [PC::from_covariance(variables.clone(), cov, n), PC::from_correlation(variables, corr, n)] {
+        pc.run(0.01);
+        assert_eq!(pc.graph.directed_edges(), expected.graph.directed_edges());
+        assert_eq!(pc.sepsets, expected.sepsets);
+    }
+    assert!(expected.graph.is_directed("A", "C") && expected.graph.is_directed("C", "D"));
+}
+
+#[test]
+#[should_panic(expected = "Correlation matrix must have a unit diagonal")]
+fn test_from_correlation_rejects_covariance() {
+    let cov = Array2::from_shape_vec((2, 2), vec![2.0, 0.5, 0.5, 1.0]).unwrap();
+    FisherZ::from_correlation(variables(), cov, 100);
+}
+
+#[test]
+#[should_panic(expected = "null values")]
+fn test_null_values_are_rejected() {
+    let df = df!(
+        "X" => [Some(1.0), Some(2.0), None, Some(4.0)],
+        "Y" => [Some(2.0), Some(1.0), Some(3.0), Some(5.0)],
+    ).unwrap();
+    FisherZ::new(&df);
+}
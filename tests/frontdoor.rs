@@ -0,0 +1,76 @@
+use polars::prelude::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rand_distr::{Distribution, Normal};
+use why_rs::dag::{Variable, DAG};
+use why_rs::dag;
+use why_rs::frontdoor::{estimate_effect, find_frontdoor_set, is_frontdoor_set};
+
+fn vars(names: &[&str]) -> Vec<Variable> {
+    names.iter().map(|n| Variable::from(*n)).collect()
+}
+
+fn smoking() -> DAG {
+    // U is an unobserved confounder of X and Y; M mediates all of X's effect
+    dag!(
+        "U" => "X",
+        "U" => "Y",
+        "X" => "M",
+        "M" => "Y"
+    )
+}
+
+#[test]
+fn test_frontdoor_criterion() {
+    let dag = smoking();
+    let x = Variable::from("X");
+    let y = Variable::from("Y");
+
+    assert!(is_frontdoor_set(&dag, &x, &y, &vars(&["M"])).unwrap());
+    assert!(!is_frontdoor_set(&dag, &x, &y, &[]).unwrap());
+
+    // A direct X -> Y edge bypasses the mediator
+    let dag = smoking().edge("X", "Y");
+    assert!(!is_frontdoor_set(&dag, &x, &y, &vars(&["M"])).unwrap());
+
+    // Confounding between X and M breaks condition (2)
+    let dag = smoking().node("W").edge("W", "X").edge("W", "M");
+    assert!(!is_frontdoor_set(&dag, &x, &y, &vars(&["M"])).unwrap());
+}
+
+#[test]
+fn test_find_frontdoor_set_skips_latents() {
+    let dag = smoking();
+    let x = Variable::from("X");
+    let y = Variable::from("Y");
+
+    assert_eq!(find_frontdoor_set(&dag, &x, &y, &vars(&["U"])).unwrap(), Some(vars(&["M"])));
+
+    // When the mediator itself is unobserved there is no front-door set
+    assert_eq!(find_frontdoor_set(&dag, &x, &y, &vars(&["U", "M"])).unwrap(), None);
+}
+
+#[test]
+fn test_frontdoor_estimate_recovers_effect() {
+    let n = 5000;
+    let mut rng = StdRng::seed_from_u64(7);
+    let noise = Normal::new(0.0, 1.0).unwrap();
+
+    let u: Vec<f64> = (0..n).map(|_| noise.sample(&mut rng)).collect();
+    let x: Vec<f64> = u.iter().map(|u| 1.5 * u + noise.sample(&mut rng)).collect();
+    let m: Vec<f64> = x.iter().map(|x| 2.0 * x + noise.sample(&mut rng)).collect();
+    let y: Vec<f64> = m.iter().zip(&u).map(|(m, u)| 3.0 * m + 4.0 * u + noise.sample(&mut rng)).collect();
+
+    let df = DataFrame::new(vec![
+        Column::from(Series::new(PlSmallStr::from("X"), x)),
+        Column::from(Series::new(PlSmallStr::from("M"), m)),
+        Column::from(Series::new(PlSmallStr::from("Y"), y)),
+    ]).unwrap();
+
+    let estimate = estimate_effect(&df, &Variable::from("X"), &Variable::from("Y"), &vars(&["M"]));
+
+    // True effect: 2.0 * 3.0
+    assert!((estimate.effect - 6.0).abs() < 0.2, "front-door effect was {}", estimate.effect);
+    assert!((estimate.treatment_to_mediator["M"] - 2.0).abs() < 0.1);
+    assert!((estimate.mediator_to_outcome["M"] - 3.0).abs() < 0.1);
+}
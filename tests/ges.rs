@@ -0,0 +1,57 @@
+mod common;
+
+use common::{edges, simulate};
+use why_rs::dag::{Variable, DAG};
+use why_rs::ges::GES;
+use why_rs::independence::FisherZ;
+use why_rs::pc::PC;
+use why_rs::pdag::PDAG;
+
+fn sorted_variables(dag: &DAG) -> Vec<Variable> {
+    let mut variables = dag.variables();
+    variables.sort();
+    variables
+}
+
+#[test]
+fn test_ges_recovers_cpdag() {
+    let (truth, df) = simulate(&[
+        ("A", "C", 0.8),
+        ("B", "C", -0.7),
+        ("C", "D", 0.9),
+        ("D", "E", 0.6),
+        ("F", "E", 0.7),
+        ("F", "G", 0.8),
+    ], 2000, 3);
+
+    let mut ges = GES::new(sorted_variables(&truth), &df);
+    ges.run();
+    assert_eq!(edges(&ges.graph), edges(&PDAG::from_dag(&truth)));
+
+    // Same answer as the constraint-based search on this data
+    let mut pc = PC::new(sorted_variables(&truth), FisherZ::new(&df));
+    pc.run(0.01);
+    assert_eq!(edges(&ges.graph), edges(&pc.graph));
+}
+
+#[test]
+fn test_backward_phase_and_penalty() {
+    // The dense shortcut A -> D makes the forward phase overshoot on some orders
+    let (truth, df) = simulate(&[
+        ("A", "B", 0.6),
+        ("C", "B", -0.8),
+        ("B", "D", 1.0),
+        ("A", "D", 0.7),
+    ], 2000, 5);
+
+    let mut ges = GES::new(sorted_variables(&truth), &df);
+    ges.run();
+    assert_eq!(edges(&ges.graph), edges(&PDAG::from_dag(&truth)));
+    let fitted = ges.score();
+
+    // A heavy penalty prefers fewer edges
+    let mut sparse = GES::new(sorted_variables(&truth), &df).penalty_discount(200.0);
+    sparse.run();
+    assert!(sparse.graph.edge_count() < ges.graph.edge_count());
+    assert!(sparse.score().is_finite() && fitted.is_finite());
+}
@@ -0,0 +1,86 @@
+use why_rs::dag::{Variable, DAG};
+use why_rs::dag;
+use why_rs::identification::{identify, IdError};
+
+fn vars(names: &[&str]) -> Vec<Variable> {
+    names.iter().map(|n| Variable::from(*n)).collect()
+}
+
+#[test]
+fn test_backdoor_adjustment_formula() {
+    let dag: DAG = dag!(
+        "Z" => "X",
+        "Z" => "Y",
+        "X" => "Y"
+    );
+
+    let estimand = identify(&dag, &[], &vars(&["Y"]), &vars(&["X"])).unwrap();
+    assert_eq!(estimand.to_string(), "Σ_{Z} P(Z) P(Y | X, Z)");
+    assert_eq!(estimand.to_latex(), "\\sum_{Z} P(Z) P(Y \\mid X, Z)");
+}
+
+#[test]
+fn test_frontdoor_formula() {
+    let dag: DAG = dag!(
+        "U" => "X",
+        "U" => "Y",
+        "X" => "M",
+        "M" => "Y"
+    );
+
+    let estimand = identify(&dag, &vars(&["U"]), &vars(&["Y"]), &vars(&["X"])).unwrap();
+    assert_eq!(estimand.to_string(), "Σ_{M} P(M | X) [Σ_{X} P(X) P(Y | M, X)]");
+}
+
+#[test]
+fn test_no_confounding_is_conditional() {
+    let dag: DAG = dag!("X" => "Y");
+
+    let estimand = identify(&dag, &[], &vars(&["Y"]), &vars(&["X"])).unwrap();
+    assert_eq!(estimand.to_string(), "P(Y | X)");
+}
+
+#[test]
+fn test_bow_graph_is_not_identifiable() {
+    // X -> Y with a hidden common cause of X and Y
+    let dag: DAG = dag!(
+        "U" => "X",
+        "U" => "Y",
+        "X" => "Y"
+    );
+
+    match identify(&dag, &vars(&["U"]), &vars(&["Y"]), &vars(&["X"])) {
+        Err(IdError::NotIdentifiable { hedge: (big, small) }) => {
+            assert_eq!(big.into_iter().collect::<Vec<_>>(), vars(&["X", "Y"]));
+            assert_eq!(small.into_iter().collect::<Vec<_>>(), vars(&["Y"]));
+        },
+        other => panic!("expected a hedge, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_napkin_graph() {
+    // W -> Z -> X -> Y, with W <-> X and W <-> Y confounded
+    let dag: DAG = dag!(
+        "W" => "Z",
+        "Z" => "X",
+        "X" => "Y",
+        "U1" => "W",
+        "U1" => "X",
+        "U2" => "W",
+        "U2" => "Y"
+    );
+
+    let estimand = identify(&dag, &vars(&["U1", "U2"]), &vars(&["Y"]), &vars(&["X"])).unwrap();
+    // Σ_w P(x, y | z, w) P(w) / Σ_w P(x | z, w) P(w), with the joint factorised
+    assert_eq!(
+        estimand.to_string(),
+        "[Σ_{W} P(W) P(X | W, Z) P(Y | W, X, Z)] / [Σ_{W, Y} P(W) P(X | W, Z) P(Y | W, X, Z)]"
+    );
+}
+
+#[test]
+fn test_unknown_variable() {
+    let dag: DAG = dag!("X" => "Y");
+    assert!(matches!(identify(&dag, &[], &vars(&["Q"]), &vars(&["X"])), Err(IdError::Graph(_))));
+}
@@ -0,0 +1,90 @@
+use polars::prelude::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rand_distr::{Distribution, Normal};
+use why_rs::dag::{Variable, DAG};
+use why_rs::dag;
+use why_rs::iv::{find_instruments, is_instrument, two_stage_least_squares, Instrument};
+
+fn vars(names: &[&str]) -> Vec<Variable> {
+    names.iter().map(|n| Variable::from(*n)).collect()
+}
+
+#[test]
+fn test_instrument_detection() {
+    // Z -> X -> Y with X and Y confounded by the latent U
+    let dag: DAG = dag!(
+        "Z" => "X",
+        "X" => "Y",
+        "U" => "X",
+        "U" => "Y"
+    );
+    let x = Variable::from("X");
+    let y = Variable::from("Y");
+
+    assert!(is_instrument(&dag, &x, &y, &Variable::from("Z"), &[]).unwrap());
+    assert_eq!(
+        find_instruments(&dag, &x, &y, &vars(&["U"])).unwrap(),
+        vec![Instrument { instrument: Variable::from("Z"), conditioning: vec![] }]
+    );
+}
+
+#[test]
+fn test_conditional_instrument() {
+    // W confounds the instrument and the outcome, so Z is only valid given W
+    let dag: DAG = dag!(
+        "Z" => "X",
+        "X" => "Y",
+        "U" => "X",
+        "U" => "Y",
+        "W" => "Z",
+        "W" => "Y"
+    );
+    let x = Variable::from("X");
+    let y = Variable::from("Y");
+    let z = Variable::from("Z");
+
+    assert!(!is_instrument(&dag, &x, &y, &z, &[]).unwrap());
+    assert!(is_instrument(&dag, &x, &y, &z, &vars(&["W"])).unwrap());
+
+    let found = find_instruments(&dag, &x, &y, &vars(&["U"])).unwrap();
+    assert!(found.contains(&Instrument { instrument: z, conditioning: vars(&["W"]) }));
+}
+
+#[test]
+fn test_two_stage_least_squares() {
+    let n = 5000;
+    let mut rng = StdRng::seed_from_u64(11);
+    let noise = Normal::new(0.0, 1.0).unwrap();
+
+    let u: Vec<f64> = (0..n).map(|_| noise.sample(&mut rng)).collect();
+    let z: Vec<f64> = (0..n).map(|_| noise.sample(&mut rng)).collect();
+    let x: Vec<f64> = z.iter().zip(&u).map(|(z, u)| z + 2.0 * u + noise.sample(&mut rng)).collect();
+    let y: Vec<f64> = x.iter().zip(&u).map(|(x, u)| 1.5 * x - 3.0 * u + noise.sample(&mut rng)).collect();
+
+    let df = DataFrame::new(vec![
+        Column::from(Series::new(PlSmallStr::from("Z"), z)),
+        Column::from(Series::new(PlSmallStr::from("X"), x)),
+        Column::from(Series::new(PlSmallStr::from("Y"), y)),
+    ]).unwrap();
+
+    let estimate = two_stage_least_squares(&df, &Variable::from("X"), &Variable::from("Y"), &vars(&["Z"]), &[]);
+
+    assert!(estimate.std_error > 0.0 && estimate.std_error < 0.2, "std error was {}", estimate.std_error);
+    assert!(
+        (estimate.effect - 1.5).abs() < 4.0 * estimate.std_error,
+        "2SLS effect was {} (se {})", estimate.effect, estimate.std_error
+    );
+    assert_eq!(estimate.n_samples, n);
+}
+
+#[test]
+#[should_panic(expected = "Need more samples than coefficients")]
+fn test_two_stage_least_squares_needs_enough_samples() {
+    let df = df!(
+        "Z" => [1.0, 2.0],
+        "X" => [2.0, 1.0],
+        "Y" => [0.5, 3.0],
+    ).unwrap();
+    two_stage_least_squares(&df, &Variable::from("X"), &Variable::from("Y"), &vars(&["Z"]), &[]);
+}
@@ -0,0 +1,74 @@
+use polars::prelude::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rand_distr::{Distribution, Normal, Uniform};
+use why_rs::independence::{CITest, FisherZ, KCI};
+use why_rs::pc::PC;
+
+fn frame(columns: Vec<(&str, Vec<f64>)>) -> DataFrame {
+    DataFrame::new(columns.into_iter()
+        .map(|(name, values)| Column::from(Series::new(PlSmallStr::from(name), values)))
+        .collect()).unwrap()
+}
+
+// X -> Z -> Y with Z = X^2 + noise and Y = cos(Z) + noise: no linear correlation between X and Z
+fn nonlinear_chain(n: usize, seed: u64) -> DataFrame {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let uniform = Uniform::new(-2.0, 2.0);
+    let noise = Normal::new(0.0, 0.2).unwrap();
+
+    let x: Vec<f64> = (0..n).map(|_| uniform.sample(&mut rng)).collect();
+    let z: Vec<f64> = x.iter().map(|x| x * x + noise.sample(&mut rng)).collect();
+    let y: Vec<f64> = z.iter().map(|z| (2.0 * z).cos() + noise.sample(&mut rng)).collect();
+    frame(vec![("X", x), ("Y", y), ("Z", z)])
+}
+
+#[test]
+fn test_hsic_detects_nonlinear_dependence() {
+    let df = nonlinear_chain(200, 1);
+    let z = ["Z".to_string()];
+
+    // Invisible to the linear test
+    assert!(FisherZ::new(&df).test("X", "Z", &[]).p_value > 0.05);
+
+    let kci = KCI::new(&df);
+    assert!(kci.test("X", "Z", &[]).p_value < 0.01);
+    assert!(kci.test("X", "Y", &[]).p_value < 0.01);
+    assert!(kci.test("X", "Y", &z).p_value > 0.05);
+}
+
+#[test]
+fn test_permutation_null() {
+    let df = nonlinear_chain(150, 2);
+    let kci = KCI::new(&df).permutations(100, 7);
+
+    // No permutation beats the observed statistic
+    assert!(kci.test("X", "Z", &[]).p_value < 0.05);
+    assert!(kci.test("X", "Y", &["Z".to_string()]).p_value > 0.05);
+
+    // Seeded, so repeatable
+    assert_eq!(kci.test("X", "Y", &[]), kci.test("X", "Y", &[]));
+}
+
+#[test]
+fn test_independent_variables() {
+    let mut rng = StdRng::seed_from_u64(3);
+    let normal = Normal::new(0.0, 1.0).unwrap();
+    let a: Vec<f64> = (0..200).map(|_| normal.sample(&mut rng)).collect();
+    let b: Vec<f64> = (0..200).map(|_| normal.sample(&mut rng)).collect();
+    let df = frame(vec![("A", a), ("B", b)]);
+
+    assert!(KCI::new(&df).test("A", "B", &[]).p_value > 0.05);
+    assert!(KCI::new(&df).permutations(200, 1).test("A", "B", &[]).p_value > 0.05);
+}
+
+#[test]
+fn test_pc_with_kci() {
+    let variables = vec!["X".to_string(), "Y".to_string(), "Z".to_string()];
+    let mut pc = PC::new(variables, KCI::new(&nonlinear_chain(200, 1)));
+    pc.run(0.05);
+
+    assert!(pc.graph.is_undirected("X", "Z"));
+    assert!(pc.graph.is_undirected("Y", "Z"));
+    assert!(!pc.graph.is_adjacent("X", "Y"));
+}
@@ -0,0 +1,109 @@
+mod common;
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use common::{variables, Oracle};
+use why_rs::independence::{CITest, CITestResult};
+use why_rs::pc::{Limit, PC};
+use why_rs::trace::TraceEvent;
+
+// Chain A -> B -> C -> D: non-adjacent pairs need a conditioning set of size 1
+fn chain(a: &str, b: &str, z: &[String]) -> bool {
+    let given = |v: &str| z.iter().any(|s| s == v);
+    match (a, b) {
+        ("A", "C") => given("B"),
+        ("B", "D") => given("C"),
+        ("A", "D") => given("B") || given("C"),
+        _ => false,
+    }
+}
+
+fn ci_tests(pc: &PC) -> Vec<TraceEvent> {
+    pc.trace.ci_tests().cloned().collect()
+}
+
+#[test]
+fn test_limit_that_is_never_hit_does_not_truncate() {
+    let mut pc = PC::new(variables(&["A", "B", "C", "D"]), Oracle(chain)).max_depth(1);
+    pc.run(0.05);
+    assert_eq!(pc.truncated, None);
+    assert!(!pc.graph.is_adjacent("A", "C") && !pc.graph.is_adjacent("A", "D"));
+}
+
+#[test]
+fn test_max_depth() {
+    let mut pc = PC::new(variables(&["A", "B", "C", "D"]), Oracle(chain)).max_depth(0);
+    pc.run(0.05);
+
+    assert_eq!(pc.truncated, Some(Limit::Depth));
+    assert!(pc.graph.is_adjacent("A", "C") && pc.graph.is_adjacent("B", "D"));
+    assert!(ci_tests(&pc).iter().all(|e| matches!(e, TraceEvent::CITest { conditioning_set, .. } if conditioning_set.is_empty())));
+}
+
+#[test]
+fn test_max_tests_keeps_the_first_tests() {
+    let mut full = PC::new(variables(&["A", "B", "C", "D"]), Oracle(chain));
+    full.run(0.05);
+    let all_tests = ci_tests(&full);
+
+    let mut pc = PC::new(variables(&["A", "B", "C", "D"]), Oracle(chain)).max_tests(8);
+    pc.run(0.05);
+
+    assert!(all_tests.len() > 8);
+    assert_eq!(pc.truncated, Some(Limit::Tests));
+    assert_eq!(ci_tests(&pc), all_tests[..8]);
+    assert!(pc.trace.events.contains(&TraceEvent::Truncated { limit: Limit::Tests }));
+    // Edges that were not separated in time stay in the graph
+    assert!(pc.graph.is_adjacent("A", "D"));
+}
+
+#[test]
+fn test_max_tests_equal_to_the_tests_needed_does_not_truncate() {
+    let mut full = PC::new(variables(&["A", "B", "C", "D"]), Oracle(chain));
+    full.run(0.05);
+    let needed = full.trace.ci_test_count;
+
+    // The budget is used up exactly, but no test is skipped
+    let mut pc = PC::new(variables(&["A", "B", "C", "D"]), Oracle(chain)).max_tests(needed);
+    pc.run(0.05);
+    assert_eq!(pc.truncated, None);
+    assert_eq!(ci_tests(&pc), ci_tests(&full));
+    assert_eq!(pc.graph.undirected_edges(), full.graph.undirected_edges());
+
+    // One test fewer does skip one
+    let mut pc = PC::new(variables(&["A", "B", "C", "D"]), Oracle(chain)).max_tests(needed - 1);
+    pc.run(0.05);
+    assert_eq!(pc.truncated, Some(Limit::Tests));
+}
+
+// Counts every test the oracle is asked to run, including ones whose result is discarded
+struct Counting(Oracle, Arc<AtomicUsize>);
+
+impl CITest for Counting {
+    fn test(&self, x: &str, y: &str, z: &[String]) -> CITestResult {
+        self.1.fetch_add(1, Ordering::Relaxed);
+        self.0.test(x, y, z)
+    }
+}
+
+#[test]
+fn test_max_tests_bounds_the_tests_computed() {
+    // Parallel batches only take the searches that fit in the tests left, so none are wasted
+    let calls = Arc::new(AtomicUsize::new(0));
+    let mut pc = PC::new(variables(&["A", "B", "C", "D"]), Counting(Oracle(chain), calls.clone())).max_tests(8);
+    pc.run(0.05);
+
+    assert_eq!(pc.trace.ci_tests().count(), 8);
+    assert!(calls.load(Ordering::Relaxed) <= 8, "{} tests computed", calls.load(Ordering::Relaxed));
+}
+
+#[test]
+fn test_time_budget() {
+    let mut pc = PC::new(variables(&["A", "B", "C", "D"]), Oracle(chain)).time_budget(Duration::ZERO);
+    pc.run(0.05);
+
+    assert_eq!(pc.truncated, Some(Limit::Time));
+    assert_eq!(pc.trace.ci_tests().count(), 0);
+    assert_eq!(pc.graph.undirected_edges().len(), 6);
+}
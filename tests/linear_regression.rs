@@ -131,4 +131,35 @@ fn test_intervention_propagation() {
     // C = 1.0 + 100.0 = 101.0
     let c_vals: Vec<f64> = df.column("C").unwrap().f64().unwrap().into_no_null_iter().collect();
     assert!(c_vals.iter().all(|&x| x == 101.0), "C should be 101.0, reflecting the intervention on B");
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_fit_with_several_parents() {
+    // C = 2A - B + 0.5E + 1, with the columns in neither sorted nor parent order
+    let n = 50;
+    let a: Vec<f64> = (0..n).map(|i| i as f64).collect();
+    let b: Vec<f64> = (0..n).map(|i| ((i * i) % 7) as f64).collect();
+    let e: Vec<f64> = (0..n).map(|i| (i as f64).sin()).collect();
+    let c: Vec<f64> = (0..n).map(|i| 2.0 * a[i] - b[i] + 0.5 * e[i] + 1.0).collect();
+    let df = DataFrame::new(vec![
+        Column::from(Series::new(PlSmallStr::from("E"), e)),
+        Column::from(Series::new(PlSmallStr::from("C"), c)),
+        Column::from(Series::new(PlSmallStr::from("B"), b)),
+        Column::from(Series::new(PlSmallStr::from("A"), a)),
+    ]).unwrap();
+
+    let fcm = FCM::from_dag(dag!(
+        "E" => "C",
+        "B" => "C",
+        "A" => "C"
+    ));
+    let mut c_lr = LinearRegression::new();
+    c_lr.fit(df, Variable::from("C"), &fcm);
+
+    // Inputs follow the sorted parents A, B, E, as in FCM::sample
+    let predict = |inputs: Vec<f64>| Mechanism::predict(&c_lr, inputs);
+    assert!((predict(vec![0.0, 0.0, 0.0]) - 1.0).abs() < 1e-6);
+    assert!((predict(vec![1.0, 0.0, 0.0]) - 3.0).abs() < 1e-6);
+    assert!((predict(vec![0.0, 1.0, 0.0]) - 0.0).abs() < 1e-6);
+    assert!((predict(vec![0.0, 0.0, 1.0]) - 1.5).abs() < 1e-6);
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use common::{dag_edges, simulate, simulate_uniform};
+use polars::prelude::*;
+use why_rs::fcm::FCM;
+use why_rs::intervention::Intervention;
+use why_rs::lingam::DirectLiNGAM;
+
+const EDGES: [(&str, &str, f64); 5] = [
+    ("D", "B", 1.5),
+    ("D", "C", -0.8),
+    ("B", "A", 0.7),
+    ("C", "A", 1.2),
+    ("A", "E", -1.0),
+];
+
+#[test]
+fn test_lingam_recovers_dag_and_weights() {
+    let (truth, df) = simulate_uniform(&EDGES, 3000, 11);
+
+    let mut lingam = DirectLiNGAM::new(vec!["A", "B", "C", "D", "E"].into_iter().map(String::from).collect(), &df);
+    lingam.run(0.01);
+
+    // Fully oriented, even where PC would leave D - B and D - C undirected
+    assert_eq!(dag_edges(&lingam.graph.dag), dag_edges(&truth));
+    assert_eq!(lingam.causal_order[0], "D");
+    assert_eq!(lingam.causal_order[4], "E");
+    for (from, to, weight) in EDGES {
+        let estimate = lingam.graph.weight(from, to).unwrap();
+        assert!((estimate - weight).abs() < 0.1, "{} -> {}: {} vs {}", from, to, estimate, weight);
+    }
+}
+
+#[test]
+fn test_weighted_dag_to_fcm() {
+    let (_, df) = simulate_uniform(&EDGES, 3000, 12);
+    let mut lingam = DirectLiNGAM::new(vec!["A", "B", "C", "D", "E"].into_iter().map(String::from).collect(), &df);
+    lingam.run(0.01);
+
+    // Weights line up with the alphabetically sorted parents B, C of A
+    let a_weights = lingam.graph.parent_weights(&"A".to_string());
+    assert!((a_weights[0] - 0.7).abs() < 0.1 && (a_weights[1] - 1.2).abs() < 0.1);
+
+    // A noiseless copy of the model reproduces the weights exactly
+    let mut fcm = FCM::from_weighted_dag(&lingam.graph, 0.0);
+    let interventional = fcm.interventional_samples(vec![
+        Intervention { variable: "B".to_string(), value: 1.0 },
+        Intervention { variable: "C".to_string(), value: 0.0 },
+    ], 5);
+    let a = interventional.column("A").unwrap().f64().unwrap().get(0).unwrap();
+    assert!((a - a_weights[0] - lingam.graph.intercept(&"A".to_string())).abs() < 1e-9);
+}
+
+#[test]
+fn test_weighted_dag_to_fcm_keeps_means() {
+    let (_, df) = simulate_uniform(&EDGES, 3000, 13);
+    // Shift every column; the model stays linear, only the intercepts change
+    let offsets = [("A", -3.0), ("B", 2.0), ("C", 0.5), ("D", 5.0), ("E", 10.0)];
+    let df = DataFrame::new(offsets.iter()
+        .map(|&(name, offset)| {
+            let values: Vec<f64> = df.column(name).unwrap().f64().unwrap().into_no_null_iter().map(|v| v + offset).collect();
+            Column::from(Series::new(PlSmallStr::from(name), values))
+        })
+        .collect()).unwrap();
+
+    let mut lingam = DirectLiNGAM::new(vec!["A", "B", "C", "D", "E"].into_iter().map(String::from).collect(), &df);
+    lingam.run(0.01);
+
+    let mut fcm = FCM::from_weighted_dag(&lingam.graph, 0.5);
+    let samples = fcm.sample(5000);
+    for (name, _) in offsets {
+        let data_mean = df.column(name).unwrap().f64().unwrap().mean().unwrap();
+        let sample_mean = samples.column(name).unwrap().f64().unwrap().mean().unwrap();
+        assert!((sample_mean - data_mean).abs() < 0.2, "{}: {} vs {}", name, sample_mean, data_mean);
+    }
+}
+
+#[test]
+fn test_gaussian_noise_is_not_identifiable() {
+    // With Gaussian noise the order of a two-variable chain is a coin flip; no crash,
+    // and the single edge is still found in one direction
+    let (_, df) = simulate(&[("X", "Y", 0.9)], 2000, 4);
+    let mut lingam = DirectLiNGAM::new(vec!["X".to_string(), "Y".to_string()], &df);
+    lingam.run(0.01);
+    assert_eq!(lingam.graph.dag.edge_count(), 1);
+    assert_eq!(lingam.graph.weights.len(), 1);
+}
+
+#[test]
+fn test_degenerate_data_skips_nodes_instead_of_panicking() {
+    // A duplicated column makes the predictors of Y collinear
+    let (_, df) = simulate_uniform(&[("X", "Y", 0.9)], 500, 5);
+    let copy = df.column("X").unwrap().clone().with_name(PlSmallStr::from("X2"));
+    let df = df.hstack(&[copy]).unwrap();
+    let mut lingam = DirectLiNGAM::new(vec!["X".to_string(), "X2".to_string(), "Y".to_string()], &df);
+    lingam.run(0.01);
+    assert_eq!(lingam.causal_order.len(), 3);
+
+    // Two samples leave no degrees of freedom for the noise variance
+    let (_, df) = simulate_uniform(&[("X", "Y", 0.9)], 2, 6);
+    let mut lingam = DirectLiNGAM::new(vec!["X".to_string(), "Y".to_string()], &df);
+    lingam.run(0.01);
+    assert_eq!(lingam.graph.dag.edge_count(), 0);
+}
@@ -0,0 +1,65 @@
+mod common;
+
+use common::{simulate, sorted_edges, variables, Oracle};
+use why_rs::dag::DAG;
+use why_rs::independence::FisherZ;
+use why_rs::knowledge::BackgroundKnowledge;
+use why_rs::pc::PC;
+use why_rs::pdag::PDAG;
+use why_rs::trace::TraceEvent;
+
+fn run_pc(edges: &[(&str, &str, f64)]) -> (DAG, PDAG) {
+    let (truth, df) = simulate(edges, 3000, 42);
+    let mut variables = truth.variables();
+    variables.sort();
+    let mut pc = PC::new(variables, FisherZ::new(&df));
+    pc.run(0.01);
+    (truth, pc.graph)
+}
+
+#[test]
+fn test_rule_two_orients_shortcut_edge() {
+    // A -> B <- C is a collider, B -> D follows from Rule 1 and A -> D needs Rule 2
+    let (truth, learned) = run_pc(&[
+        ("A", "B", 0.5),
+        ("C", "B", -0.9),
+        ("B", "D", 1.0),
+        ("A", "D", 1.0),
+    ]);
+
+    assert!(learned.is_directed("A", "D"), "A -> D should be oriented:\n{}", learned);
+    assert_eq!(sorted_edges(&learned), sorted_edges(&PDAG::from_dag(&truth)));
+}
+
+#[test]
+fn test_rule_three_orients_edge_into_collider() {
+    // C -> B <- D is a collider, A - C and A - D stay undirected, A -> B needs Rule 3
+    let (truth, learned) = run_pc(&[
+        ("A", "C", -0.9),
+        ("A", "D", -0.5),
+        ("A", "B", -1.0),
+        ("C", "B", 0.5),
+        ("D", "B", -0.9),
+    ]);
+
+    assert!(learned.is_directed("A", "B"), "A -> B should be oriented:\n{}", learned);
+    assert!(learned.is_undirected("A", "C"));
+    assert!(learned.is_undirected("A", "D"));
+    assert_eq!(sorted_edges(&learned), sorted_edges(&PDAG::from_dag(&truth)));
+}
+
+#[test]
+fn test_rule_four_alone_orients_edge() {
+    // Skeleton A - B, A - C, A - D, C - D, D - B; B and C are separated by {A, D}, so there are
+    // no colliders. With C -> D -> B required, A -> B follows from Rule 4 and from nothing else.
+    let oracle = Oracle(|a, b, z| (a, b) == ("B", "C") && z.len() == 2);
+    let knowledge = BackgroundKnowledge::new().require("C", "D").require("D", "B");
+    let mut pc = PC::new(variables(&["A", "B", "C", "D"]), oracle).knowledge(knowledge);
+    pc.run(0.05);
+
+    let rules: Vec<&TraceEvent> = pc.trace.events.iter()
+        .filter(|e| matches!(e, TraceEvent::MeekRule { .. } | TraceEvent::Collider { .. }))
+        .collect();
+    assert_eq!(rules, vec![&TraceEvent::MeekRule { rule: 4, from: "A".into(), to: "B".into() }]);
+    assert!(pc.graph.is_undirected("A", "C") && pc.graph.is_undirected("A", "D"));
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use common::{dag_edges, simulate, variables};
+use petgraph::algo::is_cyclic_directed;
+use why_rs::notears::NOTEARS;
+
+#[test]
+fn test_notears_recovers_weighted_dag() {
+    let edges = [
+        ("A", "B", 1.5),
+        ("A", "C", -1.2),
+        ("B", "D", 1.0),
+        ("C", "D", 1.3),
+        ("D", "E", -1.5),
+    ];
+    let (truth, df) = simulate(&edges, 2000, 21);
+
+    let mut notears = NOTEARS::new(variables(&["A", "B", "C", "D", "E"]), &df);
+    notears.run();
+
+    assert_eq!(dag_edges(&notears.graph.dag), dag_edges(&truth));
+    // The L1 penalty shrinks the weights towards zero
+    for (from, to, weight) in edges {
+        let estimate = notears.graph.weight(from, to).unwrap();
+        assert!(estimate.signum() == weight.signum() && (estimate - weight).abs() < 0.3,
+                "{} -> {}: {} vs {}", from, to, estimate, weight);
+    }
+}
+
+#[test]
+fn test_threshold_and_acyclicity() {
+    let (_, df) = simulate(&[("X", "Y", 0.5), ("Y", "Z", 2.0)], 1000, 22);
+
+    // The weak X -> Y edge falls under a high threshold
+    let mut sparse = NOTEARS::new(variables(&["X", "Y", "Z"]), &df).threshold(1.0);
+    sparse.run();
+    assert_eq!(sparse.graph.weights.len(), 1);
+
+    // Without any penalty or threshold the result is still a DAG
+    let mut dense = NOTEARS::new(variables(&["X", "Y", "Z"]), &df).lambda(0.0).threshold(0.0);
+    dense.run();
+    assert!(!is_cyclic_directed(&dense.graph.dag.graph));
+}
@@ -0,0 +1,135 @@
+mod common;
+
+use std::collections::BTreeSet;
+use common::{simulate, variables};
+use polars::prelude::DataFrame;
+use why_rs::dag::Variable;
+use why_rs::independence::FisherZ;
+use why_rs::pc::PC;
+use why_rs::trace::TraceEvent;
+
+type Edges = BTreeSet<(Variable, Variable)>;
+type Sepsets = Vec<((Variable, Variable), Vec<Variable>)>;
+
+// Skeleton, directed edges, undirected edges (as unordered pairs) and sepsets
+fn learn(variables: Vec<Variable>, df: &DataFrame) -> (Edges, Edges, Edges, Sepsets) {
+    let mut pc = PC::new(variables.clone(), FisherZ::new(df));
+    pc.run(0.05);
+
+    let pair = |a: &Variable, b: &Variable| if a < b { (a.clone(), b.clone()) } else { (b.clone(), a.clone()) };
+    let mut skeleton = BTreeSet::new();
+    for a in &variables {
+        for b in &variables {
+            if a < b && pc.graph.is_adjacent(a, b) { skeleton.insert((a.clone(), b.clone())); }
+        }
+    }
+    let directed = pc.graph.directed_edges().into_iter().collect();
+    let undirected = pc.graph.undirected_edges().iter().map(|(a, b)| pair(a, b)).collect();
+
+    let mut sepsets: Sepsets = pc.sepsets.iter()
+        .map(|(key, set)| {
+            let mut set: Vec<Variable> = set.iter().cloned().collect();
+            set.sort();
+            (key.clone(), set)
+        })
+        .collect();
+    sepsets.sort();
+    (skeleton, directed, undirected, sepsets)
+}
+
+// Weak effects, simulated with few samples so that several tests are borderline and the search
+// reaches conditioning sets of several sizes
+const WEAK_EDGES: [(&str, &str, f64); 9] = [
+    ("A", "B", 0.3),
+    ("A", "C", 0.25),
+    ("B", "D", 0.3),
+    ("C", "D", 0.2),
+    ("D", "E", 0.35),
+    ("B", "F", 0.15),
+    ("E", "F", 0.3),
+    ("G", "E", 0.2),
+    ("G", "C", 0.15),
+];
+
+#[test]
+fn test_result_does_not_depend_on_variable_order() {
+    let (_, df) = simulate(&WEAK_EDGES, 150, 8);
+
+    let mut variables: Vec<Variable> = ["A", "B", "C", "D", "E", "F", "G"].iter().map(|v| v.to_string()).collect();
+    let reference = learn(variables.clone(), &df);
+
+    variables.reverse();
+    assert_eq!(learn(variables.clone(), &df), reference);
+
+    for _ in 0..3 {
+        variables.rotate_left(2);
+        assert_eq!(learn(variables.clone(), &df), reference);
+    }
+}
+
+// Every trace event of PC on the weak-edges data
+fn weak_edges_trace() -> Vec<TraceEvent> {
+    let (_, df) = simulate(&WEAK_EDGES, 150, 8);
+    let mut pc = PC::new(variables(&["A", "B", "C", "D", "E", "F", "G"]), FisherZ::new(&df));
+    pc.run(0.05);
+    pc.trace.events
+}
+
+// One line of the trace fixture: `test X Y Z1,Z2 statistic p_value decision` for a CI test
+// (`-` for an empty conditioning set), `event <display>` for anything else
+enum Expected {
+    Test { x: Variable, y: Variable, conditioning_set: Vec<Variable>, statistic: f64, p_value: f64, independent: bool },
+    Event(String),
+}
+
+fn parse_fixture_line(line: &str) -> Expected {
+    if let Some(display) = line.strip_prefix("event ") {
+        return Expected::Event(display.to_string());
+    }
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    assert_eq!((fields.len(), fields[0]), (7, "test"), "Malformed fixture line: {}", line);
+    Expected::Test {
+        x: fields[1].to_string(),
+        y: fields[2].to_string(),
+        conditioning_set: if fields[3] == "-" { Vec::new() } else { fields[3].split(',').map(String::from).collect() },
+        statistic: fields[4].parse().unwrap(),
+        p_value: fields[5].parse().unwrap(),
+        independent: fields[6] == "independent",
+    }
+}
+
+#[test]
+fn test_trace_matches_sequential_fixture() {
+    // Recorded from the default (sequential) build; with the `parallel` feature on, this checks
+    // that the parallel search runs the same tests in the same order
+    let expected: Vec<Expected> = include_str!("fixtures/pc_stable_trace.txt").lines().map(parse_fixture_line).collect();
+    let actual = weak_edges_trace();
+    assert_eq!(actual.len(), expected.len());
+
+    for (i, (actual, expected)) in actual.iter().zip(&expected).enumerate() {
+        match (actual, expected) {
+            (
+                TraceEvent::CITest { x, y, conditioning_set, statistic, p_value, independent },
+                Expected::Test { x: ex, y: ey, conditioning_set: ez, statistic: es, p_value: ep, independent: ei },
+            ) => {
+                assert_eq!((x, y, conditioning_set, independent), (ex, ey, ez, ei), "Event {}", i);
+                assert!((statistic - es).abs() < 1e-6, "Event {}: statistic {} vs {}", i, statistic, es);
+                assert!((p_value - ep).abs() < 1e-6, "Event {}: p-value {} vs {}", i, p_value, ep);
+            }
+            // Other events carry no floats, so their display is compared as is
+            (actual, Expected::Event(display)) => assert_eq!(&actual.to_string(), display, "Event {}", i),
+            (actual, _) => panic!("Event {}: expected a CI test, got {}", i, actual),
+        }
+    }
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn test_parallel_skeleton_matches_single_thread() {
+    let single = rayon::ThreadPoolBuilder::new().num_threads(1).build().unwrap().install(weak_edges_trace);
+    let parallel = rayon::ThreadPoolBuilder::new().num_threads(4).build().unwrap().install(weak_edges_trace);
+
+    // Same tests in the same order, hence the same graph and sepsets
+    assert!(!single.is_empty());
+    assert_eq!(parallel, single);
+}
@@ -0,0 +1,106 @@
+use polars::prelude::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rand_distr::{Distribution, Normal};
+use why_rs::dag::{Variable, DAG};
+use why_rs::dag;
+use why_rs::independence::FisherZ;
+use why_rs::pc::PC;
+use why_rs::pdag::PDAG;
+
+type EdgeList = Vec<(Variable, Variable)>;
+
+fn edges(pdag: &PDAG) -> (EdgeList, EdgeList) {
+    let mut directed = pdag.directed_edges();
+    directed.sort();
+    let mut undirected: EdgeList = pdag.undirected_edges()
+        .into_iter()
+        .map(|(a, b)| if a < b { (a, b) } else { (b, a) })
+        .collect();
+    undirected.sort();
+    (directed, undirected)
+}
+
+fn pair(a: &str, b: &str) -> (Variable, Variable) {
+    (Variable::from(a), Variable::from(b))
+}
+
+#[test]
+fn test_chain_is_fully_undirected() {
+    let dag: DAG = dag!("A" => "B", "B" => "C");
+    let cpdag = PDAG::from_dag(&dag);
+
+    assert_eq!(edges(&cpdag), (vec![], vec![pair("A", "B"), pair("B", "C")]));
+    assert!(cpdag.to_dag().is_none());
+
+    // A->B->C, A<-B<-C and A<-B->C; the collider A->B<-C is not in the class
+    assert_eq!(cpdag.all_dags().len(), 3);
+}
+
+#[test]
+fn test_collider_orients_downstream_edges() {
+    let dag: DAG = dag!(
+        "A" => "C",
+        "B" => "C",
+        "C" => "D"
+    );
+    let cpdag = PDAG::from_dag(&dag);
+
+    assert_eq!(edges(&cpdag), (vec![pair("A", "C"), pair("B", "C"), pair("C", "D")], vec![]));
+    assert_eq!(cpdag.unshielded_colliders(), vec![(Variable::from("A"), Variable::from("C"), Variable::from("B"))]);
+
+    let member = cpdag.to_dag().expect("fully oriented CPDAG should convert to a DAG");
+    assert_eq!(member.sort().len(), 4);
+}
+
+#[test]
+fn test_members_share_the_cpdag() {
+    // Diamond with a tail: only the edges out of A are reversible
+    let dag: DAG = dag!(
+        "A" => "B",
+        "A" => "C",
+        "B" => "D",
+        "C" => "D",
+        "D" => "E"
+    );
+    let cpdag = PDAG::from_dag(&dag);
+
+    assert_eq!(edges(&cpdag), (
+        vec![pair("B", "D"), pair("C", "D"), pair("D", "E")],
+        vec![pair("A", "B"), pair("A", "C")]
+    ));
+
+    let members = cpdag.all_dags();
+    assert_eq!(members.len(), 3);
+    for member in &members {
+        assert_eq!(edges(&PDAG::from_dag(member)), edges(&cpdag));
+    }
+}
+
+#[test]
+fn test_pc_returns_pdag_without_two_cycles() {
+    let n = 2000;
+    let mut rng = StdRng::seed_from_u64(3);
+    let noise = Normal::new(0.0, 1.0).unwrap();
+
+    let a: Vec<f64> = (0..n).map(|_| noise.sample(&mut rng)).collect();
+    let b: Vec<f64> = (0..n).map(|_| noise.sample(&mut rng)).collect();
+    let c: Vec<f64> = a.iter().zip(&b).map(|(a, b)| a + b + noise.sample(&mut rng)).collect();
+    let d: Vec<f64> = c.iter().map(|c| 0.8 * c + noise.sample(&mut rng)).collect();
+
+    let df = DataFrame::new(vec![
+        Column::from(Series::new(PlSmallStr::from("A"), a)),
+        Column::from(Series::new(PlSmallStr::from("B"), b)),
+        Column::from(Series::new(PlSmallStr::from("C"), c)),
+        Column::from(Series::new(PlSmallStr::from("D"), d)),
+    ]).unwrap();
+
+    let mut pc = PC::new(vec!["A".to_string(), "B".to_string(), "C".to_string(), "D".to_string()], FisherZ::new(&df));
+    pc.run(0.01);
+
+    let truth: DAG = dag!("A" => "C", "B" => "C", "C" => "D");
+    assert_eq!(edges(&pc.graph), edges(&PDAG::from_dag(&truth)));
+
+    let learned = pc.graph.to_dag().expect("PC output should be fully oriented here");
+    assert_eq!(learned.sort().len(), 4);
+}
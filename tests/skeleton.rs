@@ -0,0 +1,28 @@
+mod common;
+
+use common::simulate;
+use why_rs::independence::FisherZ;
+use why_rs::pc::PC;
+
+#[test]
+fn test_sepset_found_among_neighbors_of_second_node() {
+    // A and D are only separated by {C, F} (or {B, F}): conditioning on B alone opens
+    // A -> B <- F -> D, and F and C are never adjacent to A. Since the pair is visited as
+    // (A, D), the separating set has to come from the neighbors of D.
+    let (truth, df) = simulate(&[
+        ("A", "B", 0.8),
+        ("F", "B", 0.8),
+        ("B", "C", 0.9),
+        ("C", "D", 0.9),
+        ("F", "D", 0.7),
+    ], 3000, 42);
+    let mut variables = truth.variables();
+    variables.sort();
+    let mut pc = PC::new(variables, FisherZ::new(&df));
+    pc.run(0.01);
+
+    assert!(!pc.graph.is_adjacent("A", "D"), "A - D should be removed:\n{}", pc.graph);
+    let sepset = &pc.sepsets[&("A".to_string(), "D".to_string())];
+    assert!(sepset.contains("F"), "Sepset of A and D should contain F, got {:?}", sepset);
+    assert_eq!(pc.graph.undirected_edges().len() + pc.graph.directed_edges().len(), 5, "{}", pc.graph);
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+use std::thread::{self, ThreadId};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use common::{variables, Oracle};
+use why_rs::pc::PC;
+use why_rs::trace::TraceEvent;
+
+// A -> C <- B, C -> D
+fn collider_chain(a: &str, b: &str, z: &[String]) -> bool {
+    match (a, b) {
+        ("A", "B") => z.is_empty(),
+        ("A", "D") | ("B", "D") => z.iter().any(|v| v == "C"),
+        _ => false,
+    }
+}
+
+// Tests run in parallel, so records are tagged with the thread that logged them
+struct CaptureLogger(Mutex<Vec<(ThreadId, Level, String)>>);
+
+impl Log for CaptureLogger {
+    fn enabled(&self, _: &Metadata) -> bool { true }
+    fn log(&self, record: &Record) {
+        self.0.lock().unwrap().push((thread::current().id(), record.level(), record.args().to_string()));
+    }
+    fn flush(&self) {}
+}
+
+static LOGGER: CaptureLogger = CaptureLogger(Mutex::new(Vec::new()));
+
+#[test]
+fn test_trace_records_tests_and_orientations() {
+    let mut pc = PC::new(variables(&["A", "B", "C", "D"]), Oracle(collider_chain));
+    pc.run(0.05);
+
+    // Every test that removed an edge is in the trace, with its decision
+    for ((x, y), result) in &pc.sepset_tests {
+        let sepset = &pc.sepsets[&(x.clone(), y.clone())];
+        assert!(pc.trace.ci_tests().any(|e| matches!(e,
+            TraceEvent::CITest { x: tx, y: ty, conditioning_set, p_value, independent: true, .. }
+                if tx == x && ty == y && *p_value == result.p_value
+                    && conditioning_set.len() == sepset.len()
+                    && conditioning_set.iter().all(|v| sepset.contains(v)))));
+    }
+    assert!(pc.trace.ci_tests().any(|e| matches!(e, TraceEvent::CITest { independent: false, .. })));
+
+    let orientations: Vec<&TraceEvent> = pc.trace.orientations().collect();
+    assert_eq!(orientations, vec![
+        &TraceEvent::Collider { x: "A".into(), y: "C".into(), z: "B".into() },
+        &TraceEvent::MeekRule { rule: 1, from: "C".into(), to: "D".into() },
+    ]);
+    assert_eq!(pc.trace.events.iter().filter(|e| matches!(e, TraceEvent::EdgeRemoved { .. })).count(), 3);
+}
+
+#[test]
+fn test_observer_and_log_receive_every_event() {
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(LevelFilter::Debug);
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&seen);
+    let mut pc = PC::new(variables(&["A", "B", "C", "D"]), Oracle(collider_chain))
+        .observer(move |event| sink.lock().unwrap().push(event.clone()));
+    pc.run(0.05);
+
+    assert_eq!(*seen.lock().unwrap(), pc.trace.events);
+
+    let logged: Vec<(Level, String)> = LOGGER.0.lock().unwrap().iter()
+        .filter(|(id, _, _)| *id == thread::current().id())
+        .map(|(_, level, msg)| (*level, msg.clone()))
+        .collect();
+    let tests = logged.iter().filter(|(level, msg)| *level == Level::Debug && msg.starts_with("CI Test")).count();
+    assert_eq!(tests, pc.trace.ci_tests().count());
+    assert!(logged.contains(&(Level::Info, "Oriented Collider: A -> C <- B".to_string())));
+    assert!(logged.contains(&(Level::Info, "Meek Rule 1: C -> D".to_string())));
+}
+
+#[test]
+fn test_trace_without_tests_keeps_the_count() {
+    let mut full = PC::new(variables(&["A", "B", "C", "D"]), Oracle(collider_chain));
+    full.run(0.05);
+
+    let seen = Arc::new(Mutex::new(0));
+    let sink = Arc::clone(&seen);
+    let mut pc = PC::new(variables(&["A", "B", "C", "D"]), Oracle(collider_chain))
+        .trace_tests(false)
+        .observer(move |event| if matches!(event, TraceEvent::CITest { .. }) { *sink.lock().unwrap() += 1; });
+    pc.run(0.05);
+
+    assert_eq!(pc.trace.ci_tests().count(), 0);
+    assert_eq!(pc.trace.ci_test_count, full.trace.ci_tests().count());
+    assert_eq!(full.trace.ci_test_count, full.trace.ci_tests().count());
+    assert_eq!(*seen.lock().unwrap(), pc.trace.ci_test_count);
+    // Everything else is still recorded
+    assert_eq!(pc.trace.orientations().collect::<Vec<_>>(), full.trace.orientations().collect::<Vec<_>>());
+}